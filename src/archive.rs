@@ -0,0 +1,327 @@
+/*
+    Created by Zoltan Kovari, 2025.
+
+    Licensed under the Apache License, Version 2.0
+    http://www.apache.org/licenses/LICENSE-2.0
+    (see LICENSE.txt)
+*/
+
+//! Downloading the per-station archives of older data, e.g. for backfilling a database.
+//!
+//! Besides the latest file, ODP publishes the data of every station in two archives:
+//! - `historical`: up to the end of the previous year, named like
+//!   `HABP_10M_13704_20050801_20241231_hist.zip` (with the first and last day of the data);
+//! - `recent`: from the end of the historical one up to now, named like `HABP_10M_13704_akt.zip`.
+
+use crate::csv::read_csv;
+use crate::download::{check_length, read_error, Client, ResponseReader};
+use crate::download::{Transport, UreqTransport};
+use crate::error::*;
+use crate::field::Dataset;
+use crate::format::CsvFormat;
+use crate::timestamp::Timestamp;
+use crate::zip::unzip_named;
+
+use std::fs::{File, OpenOptions};
+use std::io::{ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+#[cfg(test)]
+mod test;
+
+/// The two kinds of per-station archives, see the module documentation.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ArchiveKind {
+    Historical,
+    Recent,
+}
+impl ArchiveKind {
+    /// Name of the directory of the archives, under the directory of the dataset.
+    pub fn dir(&self) -> &'static str {
+        match self {
+            Self::Historical => "historical",
+            Self::Recent => "recent",
+        }
+    }
+}
+
+/// A per-station archive on ODP.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ArchiveFile {
+    pub dataset: Dataset,
+    pub kind: ArchiveKind,
+    pub station: u32,
+    /// First and last day of the data, only known for historical archives.
+    pub period: Option<(Timestamp, Timestamp)>,
+    pub name: String,
+}
+impl ArchiveFile {
+    /// Constructs the recent archive of a station, as its name is known in advance.
+    pub fn recent(dataset: Dataset, station: u32) -> Self {
+        Self {
+            dataset,
+            kind: ArchiveKind::Recent,
+            station,
+            period: None,
+            name: format!("{}_{}_akt.zip", dataset.archive_prefix(), station),
+        }
+    }
+
+    /// Tries to recognize the name of an archive in the dataset, returns `None` for any other
+    /// file.
+    pub fn from_name(dataset: Dataset, name: &str) -> Option<Self> {
+        let rest = name.strip_prefix(dataset.archive_prefix())?.strip_prefix('_')?;
+        let parts: Vec<&str> = rest.strip_suffix(".zip")?.split('_').collect();
+        let station = match parts.first() {
+            Some(s) if s.bytes().all(|b| b.is_ascii_digit()) => s.parse().ok()?,
+            _ => return None,
+        };
+        let (kind, period) = match parts[1..] {
+            ["akt"] => (ArchiveKind::Recent, None),
+            [first, last, "hist"] if first.len() == 8 && last.len() == 8 => {
+                let first = Timestamp::from_str(first).ok()?;
+                let last = Timestamp::from_str(last).ok()?;
+                (ArchiveKind::Historical, Some((first, last)))
+            }
+            _ => return None,
+        };
+
+        Some(Self {
+            dataset,
+            kind,
+            station,
+            period,
+            name: name.to_string(),
+        })
+    }
+
+    /// Path of the archive relative to `ODP_ROOT`.
+    pub fn path(&self) -> String {
+        format!("{}/{}/{}", self.dataset.dir(), self.kind.dir(), self.name)
+    }
+}
+
+/// Selects the archives holding the data of the stations between the days `from` and `to`
+/// (inclusive), in the order of the stations.
+///
+/// The historical archives are selected if their period overlaps the range. The recent archive
+/// continues the last historical one, so it is selected if the range ends after that (or if the
+/// station has no historical archive at all).
+pub fn select_archives(
+    dataset: Dataset,
+    historical: &[ArchiveFile],
+    stations: &[u32],
+    from: Timestamp,
+    to: Timestamp,
+) -> Vec<ArchiveFile> {
+    let mut selected = Vec::new();
+    for &station in stations {
+        let mut archives: Vec<&ArchiveFile> = historical
+            .iter()
+            .filter(|a| a.station == station && a.kind == ArchiveKind::Historical)
+            .collect();
+        archives.sort_by_key(|a| a.period);
+
+        let mut end = None;
+        for archive in archives {
+            if let Some((first, last)) = archive.period {
+                if first <= to && last >= from {
+                    selected.push(archive.clone());
+                }
+                end = end.max(Some(last));
+            }
+        }
+        if end.is_none_or(|end| to > end) {
+            selected.push(ArchiveFile::recent(dataset, station));
+        }
+    }
+    selected
+}
+
+/// Result of a successful archive download.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ArchiveOutcome {
+    /// The archive was downloaded, continuing a partial download if `resumed_from` is not zero.
+    Downloaded { path: PathBuf, resumed_from: u64 },
+    /// The archive was already present in the directory, it was not downloaded again.
+    Existing { path: PathBuf },
+}
+
+/// Downloads per-station archives into a directory.
+///
+/// An archive is first downloaded to a file with a `.part` suffix, which is continued with a
+/// `Range` request if present (e.g. after a failed download). When complete, it is checked that
+/// the archive holds a single CSV file with a valid header (see `CsvFormat::from_str`), then the
+/// suffix is removed. A `.part` file that fails the check (or that the server cannot continue
+/// with the requested range) is deleted, so that the next attempt starts over.
+pub struct ArchiveDownloader<T: Transport = UreqTransport> {
+    client: Client<T>,
+    dataset: Dataset,
+    dir: PathBuf,
+}
+impl<T: Transport> ArchiveDownloader<T> {
+    /// Constructs a downloader of the archives of `dataset`, into the directory `dir`.
+    pub fn new(client: Client<T>, dataset: Dataset, dir: impl Into<PathBuf>) -> Self {
+        Self {
+            client,
+            dataset,
+            dir: dir.into(),
+        }
+    }
+
+    /// Lists the historical archives of all stations, from the directory listing page.
+    pub fn list_historical(&self) -> Result<Vec<ArchiveFile>, ArchiveError> {
+        let dir = format!("{}/{}", self.dataset.dir(), ArchiveKind::Historical.dir());
+        Ok(self
+            .client
+            .list(&dir)?
+            .iter()
+            .filter(|e| !e.is_dir)
+            .filter_map(|e| ArchiveFile::from_name(self.dataset, &e.name))
+            .filter(|a| a.kind == ArchiveKind::Historical)
+            .collect())
+    }
+
+    /// Lists the historical archives, and selects the needed ones with `select_archives`.
+    pub fn select(
+        &self,
+        stations: &[u32],
+        from: Timestamp,
+        to: Timestamp,
+    ) -> Result<Vec<ArchiveFile>, ArchiveError> {
+        let historical = self.list_historical()?;
+        Ok(select_archives(self.dataset, &historical, stations, from, to))
+    }
+
+    /// Downloads and checks a single archive, unless it is already present.
+    pub fn download(&self, archive: &ArchiveFile) -> Result<ArchiveOutcome, ArchiveError> {
+        let path = self.dir.join(&archive.name);
+        if path.exists() {
+            return Ok(ArchiveOutcome::Existing { path });
+        }
+        let part = self.dir.join(format!("{}.part", archive.name));
+        let io_error = |source| ArchiveError::Io {
+            path: part.display().to_string(),
+            source,
+        };
+
+        let start = match part.metadata() {
+            Ok(metadata) => metadata.len(),
+            Err(_) => 0,
+        };
+        let url = self.client.url(&archive.path());
+        let range = format!("bytes={}-", start);
+        let headers: &[(&str, &str)] = match start {
+            0 => &[],
+            _ => &[("Range", &range)],
+        };
+        let mut response = self.client.get_reader(&url, headers)?;
+
+        // The server may also ignore the range, and send the whole file
+        let resumed = start > 0 && response.status == 206;
+        if resumed {
+            let content_range = response.header("Content-Range").unwrap_or_default();
+            if !content_range.starts_with(&format!("bytes {}-", start)) {
+                // Not continued on the next attempt, as the same range would be requested
+                std::fs::remove_file(&part).map_err(io_error)?;
+                return Err(ArchiveError::UnexpectedRange {
+                    url,
+                    start,
+                    content_range: content_range.to_string(),
+                });
+            }
+        }
+        match response.status {
+            // A range starting at the end means that the download was already complete
+            416 if start > 0 => (),
+            200 | 206 => {
+                let mut file = match resumed {
+                    true => OpenOptions::new().append(true).open(&part),
+                    false => File::create(&part),
+                }
+                .map_err(io_error)?;
+                // A truncated body is also kept, and continued on the next attempt
+                write_body(&url, &mut response, &mut file, &part)?;
+            }
+            status => return Err(DownloadError::Status { url, status }.into()),
+        }
+
+        if let Err(e) = verify(&part) {
+            std::fs::remove_file(&part).map_err(io_error)?;
+            return Err(e);
+        }
+        std::fs::rename(&part, &path).map_err(io_error)?;
+        Ok(ArchiveOutcome::Downloaded {
+            path,
+            resumed_from: if resumed { start } else { 0 },
+        })
+    }
+
+    /// Selects and downloads the archives for the stations and days, see `select_archives`.
+    ///
+    /// Every result is passed to `report`, a failed download does not stop the others. Returns
+    /// the number of failed downloads, or an error if the archives cannot be listed.
+    pub fn download_range(
+        &self,
+        stations: &[u32],
+        from: Timestamp,
+        to: Timestamp,
+        mut report: impl FnMut(&ArchiveFile, &Result<ArchiveOutcome, ArchiveError>),
+    ) -> Result<usize, ArchiveError> {
+        let mut failures = 0;
+        for archive in self.select(stations, from, to)? {
+            let result = self.download(&archive);
+            if result.is_err() {
+                failures += 1;
+            }
+            report(&archive, &result);
+        }
+        Ok(failures)
+    }
+}
+
+/// Writes the body to the file as it arrives, so that everything received is kept even if the
+/// transfer fails, then checks the length.
+fn write_body(
+    url: &str,
+    response: &mut ResponseReader,
+    file: &mut File,
+    path: &Path,
+) -> Result<(), ArchiveError> {
+    // A prematurely closed connection is detected from the length, if known (as in `get`)
+    let has_length = response.header("Content-Length").is_some();
+    let mut buf = vec![0; 64 * 1024];
+    let mut received = 0;
+    loop {
+        let n = match response.body.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof && has_length => break,
+            Err(e) => return Err(read_error(url, e).into()),
+        };
+        file.write_all(&buf[..n]).map_err(|e| ArchiveError::Io {
+            path: path.display().to_string(),
+            source: e,
+        })?;
+        received += n as u64;
+    }
+    Ok(check_length(url, response.header("Content-Length"), received)?)
+}
+
+/// Checks that the archive holds a single CSV file with a valid header, and returns its format.
+pub fn verify(path: &Path) -> Result<CsvFormat, ArchiveError> {
+    let file = File::open(path).map_err(|e| ArchiveError::Io {
+        path: path.display().to_string(),
+        source: e,
+    })?;
+    let (name, reader) = unzip_named(file).map_err(|e| ArchiveError::Zip {
+        path: path.display().to_string(),
+        source: e,
+    })?;
+    match read_csv(reader, None) {
+        Ok((_, format)) => Ok(format),
+        Err(e) => Err(ArchiveError::Csv { name, source: e }),
+    }
+}
@@ -0,0 +1,301 @@
+/*
+    Created by Zoltan Kovari, 2025.
+
+    Licensed under the Apache License, Version 2.0
+    http://www.apache.org/licenses/LICENSE-2.0
+    (see LICENSE.txt)
+*/
+
+use super::*;
+
+use crate::download::{parse_listing, Response};
+use crate::download::test::{response, serve, TEST_LISTING_HISTORICAL};
+use crate::zip::{test::TEST_ZIP, zip};
+
+use std::cell::RefCell;
+use std::io::Cursor;
+use std::rc::Rc;
+
+fn date(s: &str) -> Timestamp {
+    Timestamp::from_str(s).unwrap()
+}
+
+// Checking the archive names, and the selection by date range.
+#[test]
+fn test_archive_names() {
+    let archive = ArchiveFile::from_name(
+        Dataset::TenMinute,
+        "HABP_10M_13704_20050801_20151231_hist.zip",
+    )
+    .unwrap();
+    assert_eq!(archive.kind, ArchiveKind::Historical);
+    assert_eq!(archive.station, 13704);
+    assert_eq!(archive.period, Some((date("20050801"), date("20151231"))));
+    assert_eq!(
+        archive.path(),
+        "10_minutes/historical/HABP_10M_13704_20050801_20151231_hist.zip"
+    );
+
+    let recent = ArchiveFile::from_name(Dataset::TenMinute, "HABP_10M_13704_akt.zip").unwrap();
+    assert_eq!(recent, ArchiveFile::recent(Dataset::TenMinute, 13704));
+    assert_eq!(recent.path(), "10_minutes/recent/HABP_10M_13704_akt.zip");
+
+    for name in [
+        "HABP_10M_SYNOP_LATEST.csv.zip",
+        "HABP_1H_13704_akt.zip",
+        "HABP_10M_13704_2005080_20151231_hist.zip",
+        "HABP_10M_13704_20050801_20151231.zip",
+        "HABP_10M_x_akt.zip",
+        "HABP_10M_13704_akt.csv",
+    ] {
+        assert_eq!(ArchiveFile::from_name(Dataset::TenMinute, name), None, "{name}");
+    }
+
+    let historical: Vec<ArchiveFile> = parse_listing(TEST_LISTING_HISTORICAL)
+        .iter()
+        .filter_map(|e| ArchiveFile::from_name(Dataset::TenMinute, &e.name))
+        .collect();
+    assert_eq!(historical.len(), 3);
+    let select = |stations: &[u32], from, to| -> Vec<String> {
+        select_archives(Dataset::TenMinute, &historical, stations, date(from), date(to))
+            .into_iter()
+            .map(|a| a.name)
+            .collect()
+    };
+    assert_eq!(select(&[13704], "20150101", "20161231"), [
+        "HABP_10M_13704_20050801_20151231_hist.zip",
+        "HABP_10M_13704_20160101_20231231_hist.zip",
+    ]);
+    assert_eq!(select(&[44527, 13704], "20231231", "20240101"), [
+        "HABP_10M_44527_19960101_20231231_hist.zip",
+        "HABP_10M_44527_akt.zip",
+        "HABP_10M_13704_20160101_20231231_hist.zip",
+        "HABP_10M_13704_akt.zip",
+    ]);
+    assert_eq!(select(&[44527, 12345], "20250101", "20250110"), [
+        "HABP_10M_44527_akt.zip",
+        "HABP_10M_12345_akt.zip",
+    ]);
+    assert!(select(&[13704], "20000101", "20041231").is_empty());
+}
+
+// Checking a failed download continued with a range request, and the header verification.
+#[test]
+fn test_archive_download() {
+    let dir = std::env::temp_dir().join(format!("odp-archive-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let mut invalid = Cursor::new(Vec::new());
+    zip("Time;t;EOR\r\n".as_bytes(), &mut invalid, "HABP_10M_44527_akt.csv").unwrap();
+    let mut truncated = response(200, &[], TEST_ZIP);
+    truncated.truncate(truncated.len() - 100);
+    let start = TEST_ZIP.len() - 100;
+    let content_range = format!("bytes {}-{}/{}", start, TEST_ZIP.len() - 1, TEST_ZIP.len());
+
+    let (base_url, handle) = serve(vec![
+        response(200, &[], TEST_LISTING_HISTORICAL.as_bytes()),
+        truncated,
+        response(200, &[], invalid.get_ref()),
+        response(200, &[], TEST_LISTING_HISTORICAL.as_bytes()),
+        response(206, &[("Content-Range", &content_range)], &TEST_ZIP[start..]),
+        response(200, &[], TEST_ZIP),
+    ]);
+    let client = Client::with_transport(&base_url, UreqTransport::new());
+    let downloader = ArchiveDownloader::new(client, Dataset::TenMinute, &dir);
+    let (from, to) = (date("20231201"), date("20250110"));
+    let hist = dir.join("HABP_10M_44527_19960101_20231231_hist.zip");
+    let recent = dir.join("HABP_10M_44527_akt.zip");
+
+    let mut results = Vec::new();
+    let failures = downloader
+        .download_range(&[44527], from, to, |a, r| results.push((a.name.clone(), r.is_ok())))
+        .unwrap();
+    assert_eq!(failures, 2);
+    assert_eq!(results, [
+        ("HABP_10M_44527_19960101_20231231_hist.zip".to_string(), false),
+        ("HABP_10M_44527_akt.zip".to_string(), false),
+    ]);
+    // The truncated download is kept, the invalid one is deleted
+    let part = |path: &Path| PathBuf::from(format!("{}.part", path.display()));
+    assert_eq!(std::fs::metadata(part(&hist)).unwrap().len(), start as u64);
+    assert!(!part(&recent).exists());
+
+    let mut outcomes = Vec::new();
+    let failures = downloader
+        .download_range(&[44527], from, to, |_, r| outcomes.push(r.as_ref().unwrap().clone()))
+        .unwrap();
+    assert_eq!(failures, 0);
+    assert_eq!(outcomes, [
+        ArchiveOutcome::Downloaded {
+            path: hist.clone(),
+            resumed_from: start as u64,
+        },
+        ArchiveOutcome::Downloaded {
+            path: recent.clone(),
+            resumed_from: 0,
+        },
+    ]);
+    assert_eq!(std::fs::read(&hist).unwrap(), TEST_ZIP);
+    assert!(verify(&recent).is_ok());
+
+    let requests = handle.join().unwrap();
+    assert!(requests[4].contains(&format!("\r\nRange: bytes={}-\r\n", start)));
+    assert!(!requests[5].contains("Range:"));
+
+    // Nothing is requested for the present archives
+    let archive = ArchiveFile::recent(Dataset::TenMinute, 44527);
+    assert_eq!(downloader.download(&archive).unwrap(), ArchiveOutcome::Existing { path: recent });
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Stand-in transport serving `TEST_ZIP`, whose first response breaks off after `fail_at` bytes
+/// of the body. The `Range` header of every request is recorded.
+struct BrokenTransport {
+    fail_at: usize,
+    ranges: Rc<RefCell<Vec<Option<String>>>>,
+}
+impl Transport for BrokenTransport {
+    fn get(&self, _url: &str, _headers: &[(&str, &str)]) -> Result<Response, DownloadError> {
+        unreachable!("the archives are downloaded with get_reader")
+    }
+
+    fn get_reader(
+        &self,
+        _url: &str,
+        headers: &[(&str, &str)],
+    ) -> Result<ResponseReader, DownloadError> {
+        let range = headers.iter().find(|(n, _)| *n == "Range").map(|(_, v)| v.to_string());
+        let first = self.ranges.borrow().is_empty();
+        self.ranges.borrow_mut().push(range);
+
+        let length = ("Content-Length".to_string(), TEST_ZIP.len().to_string());
+        if first {
+            let broken = std::io::Error::new(ErrorKind::ConnectionReset, "connection reset");
+            let body = Cursor::new(&TEST_ZIP[..self.fail_at]).chain(BrokenReader(Some(broken)));
+            return Ok(ResponseReader {
+                status: 200,
+                headers: vec![length],
+                body: Box::new(body),
+            });
+        }
+        let (start, len) = (self.fail_at, TEST_ZIP.len());
+        let content_range = format!("bytes {}-{}/{}", start, len - 1, len);
+        Ok(ResponseReader {
+            status: 206,
+            headers: vec![("Content-Range".to_string(), content_range)],
+            body: Box::new(Cursor::new(&TEST_ZIP[start..])),
+        })
+    }
+}
+
+/// Reader failing with the given error, once.
+struct BrokenReader(Option<std::io::Error>);
+impl Read for BrokenReader {
+    fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+        match self.0.take() {
+            Some(e) => Err(e),
+            None => Ok(0),
+        }
+    }
+}
+
+// Checking that the bytes received before a failed transfer are kept, and continued from.
+#[test]
+fn test_archive_download_broken() {
+    let dir = std::env::temp_dir().join(format!("odp-archive-broken-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let fail_at = TEST_ZIP.len() / 2;
+    let ranges = Rc::new(RefCell::new(Vec::new()));
+    let transport = BrokenTransport {
+        fail_at,
+        ranges: ranges.clone(),
+    };
+    let client = Client::with_transport("http://localhost/", transport);
+    let downloader = ArchiveDownloader::new(client, Dataset::TenMinute, &dir);
+    let archive = ArchiveFile::recent(Dataset::TenMinute, 44527);
+    let path = dir.join(&archive.name);
+    let part = dir.join(format!("{}.part", archive.name));
+
+    assert!(matches!(
+        downloader.download(&archive),
+        Err(ArchiveError::Download(DownloadError::Io { .. }))
+    ));
+    assert_eq!(std::fs::read(&part).unwrap(), &TEST_ZIP[..fail_at]);
+
+    assert_eq!(downloader.download(&archive).unwrap(), ArchiveOutcome::Downloaded {
+        path: path.clone(),
+        resumed_from: fail_at as u64,
+    });
+    assert_eq!(std::fs::read(&path).unwrap(), TEST_ZIP);
+
+    assert_eq!(*ranges.borrow(), [None, Some(format!("bytes={}-", fail_at))]);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Stand-in transport answering a range request with the wrong range, and any other request
+/// with the whole `TEST_ZIP`. The `Range` header of every request is recorded.
+struct WrongRangeTransport {
+    ranges: Rc<RefCell<Vec<Option<String>>>>,
+}
+impl Transport for WrongRangeTransport {
+    fn get(&self, _url: &str, _headers: &[(&str, &str)]) -> Result<Response, DownloadError> {
+        unreachable!("the archives are downloaded with get_reader")
+    }
+
+    fn get_reader(
+        &self,
+        _url: &str,
+        headers: &[(&str, &str)],
+    ) -> Result<ResponseReader, DownloadError> {
+        let range = headers.iter().find(|(n, _)| *n == "Range").map(|(_, v)| v.to_string());
+        let (status, headers) = match range {
+            Some(_) => {
+                let content_range = format!("bytes 0-{}/{}", TEST_ZIP.len() - 1, TEST_ZIP.len());
+                (206, vec![("Content-Range".to_string(), content_range)])
+            }
+            None => (200, Vec::new()),
+        };
+        self.ranges.borrow_mut().push(range);
+        Ok(ResponseReader {
+            status,
+            headers,
+            body: Box::new(Cursor::new(TEST_ZIP)),
+        })
+    }
+}
+
+// Checking that a partial download the server cannot continue is started over.
+#[test]
+fn test_archive_download_wrong_range() {
+    let dir = std::env::temp_dir().join(format!("odp-archive-range-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let ranges = Rc::new(RefCell::new(Vec::new()));
+    let transport = WrongRangeTransport {
+        ranges: ranges.clone(),
+    };
+    let client = Client::with_transport("http://localhost/", transport);
+    let downloader = ArchiveDownloader::new(client, Dataset::TenMinute, &dir);
+    let archive = ArchiveFile::recent(Dataset::TenMinute, 44527);
+    let path = dir.join(&archive.name);
+    let part = dir.join(format!("{}.part", archive.name));
+    std::fs::write(&part, &TEST_ZIP[..100]).unwrap();
+
+    assert!(matches!(
+        downloader.download(&archive),
+        Err(ArchiveError::UnexpectedRange { start: 100, .. })
+    ));
+    assert!(!part.exists());
+
+    assert_eq!(downloader.download(&archive).unwrap(), ArchiveOutcome::Downloaded {
+        path: path.clone(),
+        resumed_from: 0,
+    });
+    assert_eq!(std::fs::read(&path).unwrap(), TEST_ZIP);
+    assert_eq!(*ranges.borrow(), [Some("bytes=100-".to_string()), None]);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
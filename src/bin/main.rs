@@ -1,13 +0,0 @@
-/*
-    Created by Zoltan Kovari, 2024.
-
-    Licensed under the Apache License, Version 2.0
-    http://www.apache.org/licenses/LICENSE-2.0
-    (see LICENSE.txt)
-*/
-
-use hungaromet_odp_client::*;
-
-fn main() {
-    hello();
-}
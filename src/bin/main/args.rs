@@ -0,0 +1,375 @@
+/*
+    Created by Zoltan Kovari, 2024.
+
+    Licensed under the Apache License, Version 2.0
+    http://www.apache.org/licenses/LICENSE-2.0
+    (see LICENSE.txt)
+*/
+
+//! Parsing and checking the command-line arguments.
+
+use hungaromet_odp_client::config::{self, Config, TeeConfig};
+use hungaromet_odp_client::copy::CopyFormat;
+use hungaromet_odp_client::field::Dataset;
+use hungaromet_odp_client::format::*;
+use hungaromet_odp_client::sink::SinkKind;
+use hungaromet_odp_client::sql::Dialect;
+use hungaromet_odp_client::timestamp::Timestamp;
+
+use std::error::Error;
+use std::str::FromStr;
+
+#[cfg(test)]
+mod test;
+
+pub(crate) const USAGE: &str = "\
+Usage: hungaromet-odp-client [OPTIONS]
+
+Downloads the latest 10-minute synoptic data from HungaroMet ODP (by default), and prints it as
+CSV to the standard output.
+
+Settings not given on the command line are taken from the configuration file, if any.
+
+Configuration:
+    --config <FILE>         Read settings from an INI-style configuration file
+    --print-config          Print the effective settings in the configuration file format
+                            and exit
+
+Input (zip archives are recognized automatically):
+    --base-url <URL>        Base URL of the ODP server, for the latest data and '--path'
+    --cache <DIR>           Keep the downloads in a cache directory, and only download them
+                            again if they changed on the server
+    --url <URL>             Download from an absolute URL
+    --path <PATH>           Download from a path relative to the ODP root
+    --input <FILE>          Read a CSV or zip file, '-' for the standard input
+    --input-zip <FILE>      Read a zip file, '-' for the standard input
+
+Archives:
+    --download-archives <DIR>
+                            Download the per-station archives (historical and recent) holding
+                            the data between '--from' and '--to' into a directory, instead of
+                            converting. Needs '--stations' with station numbers only
+    --from <DATE>           First day of the archive data, as YYYYMMDD
+    --to <DATE>             Last day of the archive data, as YYYYMMDD [default: today]
+
+Output:
+    --output <FILE>         Write to a file instead of the standard output
+    --append                Append to the output file, skipping records already present in it
+    --daemon                Keep running, and append the records of every new latest file to
+                            the CSV output (and database) files, until terminated
+    --validate              Print a validation report of the input instead of converting it,
+                            the exit status is 1 if any issues were found
+    --report-format <FMT>   Validation report format: text or json [default: text]
+    --sql <DIALECT>         Write an SQL script (sqlite or postgres) that creates the `data` table
+                            and inserts the records, instead of CSV
+    --copy <FMT>            Write a psql script that creates the `data` table and loads the
+                            records with COPY in text or csv format, instead of CSV
+    --tee <KIND[,OPTION=VALUE...]:FILE>
+                            Also write to a file, can be repeated. KIND is one of csv,
+                            sql-sqlite, sql-postgres, copy-text, copy-csv, or sqlite (a database
+                            file, if supported). The format is the same as the main output,
+                            except for the options given, which are the format and filter keys
+                            of the configuration file (e.g. 'csv,alignment=true,missing=null')
+
+Format:
+    --dataset <NAME>        Dataset of the input: 10_minutes, hourly or daily, field indices
+                            and ranges refer to its columns [default: 10_minutes]
+    --delimiter <CHAR>      Field separator character, 'tab' or 'space' for whitespace
+                            [default: ;]
+    --condensed             Do not pad the fields to their default widths
+    --missing <VALUE>       Missing value representation: -999, null or empty [default: -999]
+
+Filter:
+    --stations <LIST>       Comma-separated list of station numbers and names,
+                            names may use SQL LIKE patterns (e.g. 'Budapest%')
+    --invert-stations       Keep the stations not in the list
+    --include <LIST>        Fields to include, as titles, indices, ranges or patterns
+                            (e.g. 't,ta,6-9,Q_*,fx..fxs')
+    --exclude <LIST>        Fields to exclude, same syntax as for '--include'
+    --no-info               Exclude station info fields (name and location)
+    --no-values             Exclude measurement value fields
+    --no-q                  Exclude Q-fields
+    --no-eor                Exclude the EOR marker field
+
+    -h, --help              Print this help
+    -V, --version           Print version
+";
+
+/// Where to read the input from.
+#[derive(Debug)]
+pub(crate) enum Input {
+    Latest,
+    Url(String),
+    Path(String),
+    File { name: String, zip: bool },
+}
+
+/// Parsed command-line options, the ones that can also be given in the configuration file are
+/// in `config`.
+#[derive(Debug)]
+pub(crate) struct Options {
+    pub(crate) input: Input,
+    pub(crate) daemon: bool,
+    pub(crate) validate: bool,
+    pub(crate) json_report: bool,
+    pub(crate) sql: Option<Dialect>,
+    pub(crate) copy: Option<CopyFormat>,
+    pub(crate) archives: Option<String>,
+    pub(crate) from: Option<Timestamp>,
+    pub(crate) to: Option<Timestamp>,
+    pub(crate) config: Config,
+}
+impl Options {
+    fn new(config: Config) -> Self {
+        Self {
+            input: Input::Latest,
+            daemon: false,
+            validate: false,
+            json_report: false,
+            sql: None,
+            copy: None,
+            archives: None,
+            from: None,
+            to: None,
+            config,
+        }
+    }
+}
+
+/// What to do after parsing the arguments.
+#[derive(Debug)]
+pub(crate) enum Command {
+    Run(Options),
+    PrintConfig(Config),
+    Help,
+    Version,
+}
+
+/// Parses the arguments (without the program name), after loading the configuration file given
+/// with `--config`, so that the arguments override it.
+pub(crate) fn parse_args(args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let args: Vec<String> = args.collect();
+    let config = match find_config(&args) {
+        Some(path) => Config::load(path).map_err(|e| error_chain(&e))?,
+        None => Config::default(),
+    };
+    let mut options = Options::new(config);
+    let mut print_config = false;
+
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        let (flag, inline) = match arg.split_once('=') {
+            Some((f, v)) if f.starts_with("--") => (f.to_string(), Some(v.to_string())),
+            _ => (arg, None),
+        };
+        let mut value = || {
+            inline
+                .clone()
+                .or_else(|| args.next())
+                .ok_or(format!("missing value for '{}'", flag))
+        };
+
+        match flag.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-V" | "--version" => return Ok(Command::Version),
+            "--config" => drop(value()?),
+            "--print-config" => print_config = true,
+            "--base-url" => options.config.base_url = value()?,
+            "--cache" => options.config.cache = Some(value()?),
+            "--url" => options.input = Input::Url(value()?),
+            "--path" => options.input = Input::Path(value()?),
+            "--input" => options.input = Input::File {
+                name: value()?,
+                zip: false,
+            },
+            "--input-zip" => options.input = Input::File {
+                name: value()?,
+                zip: true,
+            },
+            "--download-archives" => options.archives = Some(value()?),
+            "--from" => options.from = Some(parse_date(&value()?)?),
+            "--to" => options.to = Some(parse_date(&value()?)?),
+            "--output" => options.config.output = Some(value()?),
+            "--append" => options.config.append = true,
+            "--daemon" => options.daemon = true,
+            "--validate" => options.validate = true,
+            "--report-format" => match value()?.as_str() {
+                "text" => options.json_report = false,
+                "json" => options.json_report = true,
+                other => return Err(format!("unknown report format '{}'", other)),
+            },
+            "--sql" => options.sql = Some(parse_dialect(&value()?)?),
+            "--copy" => match value()?.as_str() {
+                "text" => options.copy = Some(CopyFormat::Text),
+                "csv" => options.copy = Some(CopyFormat::Csv),
+                other => return Err(format!("unknown COPY format '{}'", other)),
+            },
+            "--tee" => options.config.tees.push(parse_tee(&value()?)?),
+            "--dataset" => options.config.dataset = parse_dataset(&value()?)?,
+            "--delimiter" => options.config.delimiter = parse_delimiter(&value()?)?,
+            "--condensed" => options.config.alignment = false,
+            "--missing" => options.config.missing = parse_missing(&value()?)?,
+            "--stations" => options.config.stations = Some(value()?),
+            "--invert-stations" => options.config.invert_stations = true,
+            "--include" => options.config.include = Some(value()?),
+            "--exclude" => options.config.exclude = Some(value()?),
+            "--no-info" => options.config.info = false,
+            "--no-values" => options.config.values = false,
+            "--no-q" => options.config.q = false,
+            "--no-eor" => options.config.eor = false,
+            _ => return Err(format!("unexpected argument '{}'", flag)),
+        }
+    }
+
+    let config = &options.config;
+    if config.append && config.output.is_none() {
+        return Err("'--append' requires '--output'".to_string());
+    }
+    if config.append && options.validate {
+        return Err("'--append' cannot be used with '--validate'".to_string());
+    }
+    if options.sql.is_some() && options.copy.is_some() {
+        return Err("'--sql' cannot be used with '--copy'".to_string());
+    }
+    let script = options.sql.is_some() || options.copy.is_some();
+    if script && (config.append || options.validate) {
+        let msg = "'--sql' and '--copy' cannot be used with '--append' or '--validate'";
+        return Err(msg.to_string());
+    }
+    if options.validate && !(config.tees.is_empty() && config.database.is_none()) {
+        return Err("'--tee' and a database cannot be used with '--validate'".to_string());
+    }
+    if options.archives.is_some() {
+        check_archives(&options)?;
+    } else if options.from.is_some() || options.to.is_some() {
+        return Err("'--from' and '--to' require '--download-archives'".to_string());
+    }
+    let latest = matches!(options.input, Input::Latest) && options.archives.is_none();
+    if config.dataset != Dataset::TenMinute && latest {
+        let msg = "the latest data is only available in the 10_minutes dataset";
+        return Err(msg.to_string());
+    }
+    if options.daemon {
+        check_daemon(&options)?;
+    }
+    #[cfg(not(feature = "sqlite"))]
+    if config.database.is_some() {
+        return Err("database output is not supported in this build".to_string());
+    }
+
+    match print_config {
+        true => Ok(Command::PrintConfig(options.config)),
+        false => Ok(Command::Run(options)),
+    }
+}
+
+/// Checks the options that are not supported in daemon mode.
+fn check_daemon(options: &Options) -> Result<(), String> {
+    if !matches!(options.input, Input::Latest) {
+        return Err("'--daemon' cannot be used with the input options".to_string());
+    }
+    if options.validate || options.sql.is_some() || options.copy.is_some() {
+        let msg = "'--daemon' cannot be used with '--validate', '--sql' or '--copy'";
+        return Err(msg.to_string());
+    }
+    if options.config.output.is_none() {
+        return Err("'--daemon' requires '--output'".to_string());
+    }
+    if options.config.tees.iter().any(|tee| !is_daemon_kind(tee.kind)) {
+        return Err("'--daemon' only supports csv and sqlite with '--tee'".to_string());
+    }
+    Ok(())
+}
+
+/// Checks the options that are not supported when downloading archives.
+fn check_archives(options: &Options) -> Result<(), String> {
+    let config = &options.config;
+    let converting = options.daemon || options.validate || options.sql.is_some();
+    let output = config.output.is_some() || !config.tees.is_empty() || config.database.is_some();
+    if !matches!(options.input, Input::Latest) || converting || options.copy.is_some() || output {
+        let msg = "'--download-archives' cannot be used with other input or output options";
+        return Err(msg.to_string());
+    }
+    if options.from.is_none() {
+        return Err("'--download-archives' requires '--from'".to_string());
+    }
+    match &config.stations {
+        Some(stations) => parse_station_numbers(stations).map(|_| ()),
+        None => Err("'--download-archives' requires '--stations'".to_string()),
+    }
+}
+
+/// Whether the output kind can be appended to.
+fn is_daemon_kind(kind: SinkKind) -> bool {
+    match kind {
+        SinkKind::Csv => true,
+        #[cfg(feature = "sqlite")]
+        SinkKind::Sqlite => true,
+        _ => false,
+    }
+}
+
+/// Returns the path given with the last `--config`, before parsing the rest of the arguments.
+fn find_config(args: &[String]) -> Option<&str> {
+    let mut path = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.strip_prefix("--config=") {
+            Some(p) => path = Some(p),
+            None if arg == "--config" => path = args.next().map(String::as_str).or(path),
+            None => {}
+        }
+    }
+    path
+}
+
+fn parse_delimiter(s: &str) -> Result<Delimiter, String> {
+    config::parse_delimiter(s).map_err(|e| format!("invalid '--delimiter', {}", e))
+}
+
+/// Parses a comma-separated list of station numbers, for the archive names.
+pub(crate) fn parse_station_numbers(s: &str) -> Result<Vec<u32>, String> {
+    s.split(',')
+        .map(|item| {
+            let item = item.trim();
+            item.parse()
+                .map_err(|_| format!("invalid station number '{}', archives need numbers", item))
+        })
+        .collect()
+}
+
+fn parse_date(s: &str) -> Result<Timestamp, String> {
+    match s.len() {
+        8 => Timestamp::from_str(s).map_err(|e| e.to_string()),
+        _ => Err(format!("invalid date '{}', expected YYYYMMDD", s)),
+    }
+}
+
+fn parse_dataset(s: &str) -> Result<Dataset, String> {
+    Dataset::try_from(s).map_err(|e| e.to_string())
+}
+
+fn parse_dialect(s: &str) -> Result<Dialect, String> {
+    Dialect::try_from(s).map_err(|e| e.to_string())
+}
+
+fn parse_tee(s: &str) -> Result<TeeConfig, String> {
+    TeeConfig::from_str(s).map_err(|e| format!("invalid '--tee', {}", error_chain(&e)))
+}
+
+fn parse_missing(s: &str) -> Result<MissingValue, String> {
+    config::parse_missing(s).map_err(|e| e.to_string())
+}
+
+/// Joins the message of an error with the messages of its sources.
+fn error_chain(e: &dyn Error) -> String {
+    let mut msg = e.to_string();
+    let mut source = e.source();
+    while let Some(e) = source {
+        msg.push_str(", ");
+        msg.push_str(&e.to_string());
+        source = e.source();
+    }
+    msg
+}
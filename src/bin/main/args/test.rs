@@ -0,0 +1,158 @@
+/*
+    Created by Zoltan Kovari, 2024.
+
+    Licensed under the Apache License, Version 2.0
+    http://www.apache.org/licenses/LICENSE-2.0
+    (see LICENSE.txt)
+*/
+
+use super::*;
+
+fn parse(args: &str) -> Result<Command, String> {
+    parse_args(args.split_whitespace().map(String::from))
+}
+
+fn run_options(args: &str) -> Options {
+    match parse(args) {
+        Ok(Command::Run(options)) => options,
+        other => panic!("unexpected result {:?} for '{}'", other, args),
+    }
+}
+
+// Checking the parsed values, and the inline form of the values.
+#[test]
+fn test_parse_args() {
+    let options = run_options("");
+    assert!(matches!(options.input, Input::Latest));
+    assert_eq!(options.config, Config::default());
+
+    let options = run_options(
+        "--input-zip - --output=out.csv --append --delimiter tab --condensed --missing empty \
+         --stations 44527,Budapest% --invert-stations --include=t,ta --no-q --tee csv:a.csv \
+         --tee csv,alignment=true,include=t,ta:b.csv",
+    );
+    assert!(matches!(options.input, Input::File { ref name, zip: true } if name == "-"));
+    let config = &options.config;
+    assert_eq!(config.output.as_deref(), Some("out.csv"));
+    assert!(config.append && !config.alignment && config.invert_stations && !config.q);
+    assert_eq!(config.delimiter, Delimiter::try_from('\t').unwrap());
+    assert_eq!(config.missing, MissingValue::Empty);
+    assert_eq!(config.stations.as_deref(), Some("44527,Budapest%"));
+    assert_eq!(config.include.as_deref(), Some("t,ta"));
+    assert_eq!(config.tees[0], TeeConfig::new(SinkKind::Csv, "a.csv"));
+    assert_eq!(config.tees[1].alignment, Some(true));
+    assert_eq!(config.tees[1].include.as_deref(), Some("t,ta"));
+    // The same names as in the configuration file
+    let config = run_options("--delimiter space").config;
+    assert_eq!(config.delimiter, Delimiter::try_from(' ').unwrap());
+    assert!(config.to_string().contains("\ndelimiter = space\n"));
+
+    let options = run_options("--download-archives dir --stations 44527 --from 20240101");
+    assert_eq!(options.archives.as_deref(), Some("dir"));
+    assert_eq!(options.from, Some(Timestamp::from_str("20240101").unwrap()));
+    assert_eq!(options.to, None);
+
+    assert!(matches!(parse("--print-config --no-eor"), Ok(Command::PrintConfig(c)) if !c.eor));
+    assert!(matches!(parse("--no-eor -h"), Ok(Command::Help)));
+    assert!(matches!(parse("-V"), Ok(Command::Version)));
+}
+
+// Checking the invalid arguments and values.
+#[test]
+fn test_parse_args_errors() {
+    for (args, msg) in [
+        ("--unknown", "unexpected argument '--unknown'"),
+        ("--output", "missing value for '--output'"),
+        ("--delimiter ab", "invalid '--delimiter', expected a single character, \
+                            \"tab\" or \"space\""),
+        ("--report-format xml", "unknown report format 'xml'"),
+        ("--copy binary", "unknown COPY format 'binary'"),
+        ("--tee csv", "invalid '--tee', expected KIND[,OPTION=VALUE...]:FILE, found \"csv\""),
+        ("--tee csv,eor=1:a", "invalid '--tee', invalid value for output option \"eor\", \
+                       provided string was not `true` or `false`"),
+        ("--from 2024010100", "invalid date '2024010100', expected YYYYMMDD"),
+    ] {
+        assert_eq!(parse(args).unwrap_err(), msg, "{}", args);
+    }
+    for args in ["--sql mysql", "--tee xml:a.xml", "--dataset weekly", "--missing nil"] {
+        assert!(parse(args).is_err(), "{}", args);
+    }
+    for args in ["--tee csv:", "--tee csv,quote=true:a.csv", "--tee csv,t:a.csv"] {
+        assert!(parse(args).is_err(), "{}", args);
+    }
+}
+
+// Checking the options that cannot be used together.
+#[test]
+fn test_parse_args_conflicts() {
+    for (args, msg) in [
+        ("--append", "'--append' requires '--output'"),
+        ("--output a --append --validate", "'--append' cannot be used with '--validate'"),
+        ("--sql sqlite --copy text", "'--sql' cannot be used with '--copy'"),
+        (
+            "--output a --append --sql postgres",
+            "'--sql' and '--copy' cannot be used with '--append' or '--validate'",
+        ),
+        (
+            "--validate --copy csv",
+            "'--sql' and '--copy' cannot be used with '--append' or '--validate'",
+        ),
+        ("--validate --tee csv:a", "'--tee' and a database cannot be used with '--validate'"),
+        ("--from 20240101", "'--from' and '--to' require '--download-archives'"),
+        ("--to 20240101", "'--from' and '--to' require '--download-archives'"),
+        (
+            "--dataset daily",
+            "the latest data is only available in the 10_minutes dataset",
+        ),
+        ("--daemon", "'--daemon' requires '--output'"),
+        (
+            "--daemon --output a --input b",
+            "'--daemon' cannot be used with the input options",
+        ),
+        (
+            "--daemon --output a --validate",
+            "'--daemon' cannot be used with '--validate', '--sql' or '--copy'",
+        ),
+        (
+            "--daemon --output a --sql sqlite",
+            "'--daemon' cannot be used with '--validate', '--sql' or '--copy'",
+        ),
+        (
+            "--daemon --output a --tee copy-csv:b",
+            "'--daemon' only supports csv and sqlite with '--tee'",
+        ),
+        (
+            "--download-archives d --stations 1",
+            "'--download-archives' requires '--from'",
+        ),
+        (
+            "--download-archives d --from 20240101",
+            "'--download-archives' requires '--stations'",
+        ),
+        (
+            "--download-archives d --from 20240101 --stations Budapest",
+            "invalid station number 'Budapest', archives need numbers",
+        ),
+        (
+            "--download-archives d --from 20240101 --stations 1 --output a",
+            "'--download-archives' cannot be used with other input or output options",
+        ),
+        (
+            "--download-archives d --from 20240101 --stations 1 --url u",
+            "'--download-archives' cannot be used with other input or output options",
+        ),
+    ] {
+        assert_eq!(parse(args).unwrap_err(), msg, "{}", args);
+    }
+
+    // Allowed combinations
+    for args in [
+        "--dataset daily --input a.csv",
+        "--dataset hourly --download-archives d --from 20240101 --stations 1",
+        "--daemon --output a --tee csv:b",
+        "--validate --output report.txt",
+        "--sql sqlite --output a.sql --tee csv:b",
+    ] {
+        assert!(parse(args).is_ok(), "{}", args);
+    }
+}
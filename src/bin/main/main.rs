@@ -0,0 +1,276 @@
+/*
+    Created by Zoltan Kovari, 2024.
+
+    Licensed under the Apache License, Version 2.0
+    http://www.apache.org/licenses/LICENSE-2.0
+    (see LICENSE.txt)
+*/
+
+mod args;
+
+use args::*;
+
+use hungaromet_odp_client::archive::*;
+use hungaromet_odp_client::csv::*;
+use hungaromet_odp_client::daemon::*;
+#[cfg(feature = "sqlite")]
+use hungaromet_odp_client::db::Database;
+use hungaromet_odp_client::download::Client;
+use hungaromet_odp_client::error::SinkError;
+use hungaromet_odp_client::format::*;
+use hungaromet_odp_client::record::*;
+use hungaromet_odp_client::sink::*;
+use hungaromet_odp_client::timestamp::Timestamp;
+use hungaromet_odp_client::zip::{unzip, unzip_stream};
+
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Cursor, Write};
+use std::process::ExitCode;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn main() -> ExitCode {
+    let command = match parse_args(std::env::args().skip(1)) {
+        Ok(c) => c,
+        Err(msg) => {
+            eprintln!("error: {}\n\nFor more information, try '--help'.", msg);
+            return ExitCode::from(2);
+        }
+    };
+
+    let result = match command {
+        Command::Help => {
+            print!("{}", USAGE);
+            Ok(())
+        }
+        Command::Version => {
+            println!("hungaromet-odp-client {}", env!("CARGO_PKG_VERSION"));
+            Ok(())
+        }
+        Command::PrintConfig(config) => {
+            print!("{}", config);
+            Ok(())
+        }
+        Command::Run(options) => run(&options),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            print_error(e.as_ref());
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(options: &Options) -> Result<(), Box<dyn Error>> {
+    let config = &options.config;
+    let record_filter = config.record_filter()?;
+    let format = config.csv_format()?;
+
+    if options.daemon {
+        return run_daemon(options, record_filter.as_ref(), &format);
+    }
+    if let Some(dir) = &options.archives {
+        return run_archives(options, dir);
+    }
+
+    let reader = open_input(&options.input, &config.client())?;
+    if options.validate {
+        return validate(reader, options, record_filter.as_ref());
+    }
+    let kind = match (options.sql, options.copy) {
+        (Some(dialect), _) => SinkKind::Sql(dialect),
+        (_, Some(copy_format)) => SinkKind::Copy(copy_format),
+        _ => SinkKind::Csv,
+    };
+
+    let mut tee = Tee::new();
+    tee.push(open_sink(kind, config.output.as_deref(), format.clone(), config.append)?);
+    for tee_config in &config.tees {
+        let format = config.tee_format(tee_config)?;
+        tee.push(open_sink(tee_config.kind, Some(&tee_config.path), format, false)?);
+    }
+    #[cfg(feature = "sqlite")]
+    if let Some(path) = &config.database {
+        tee.push(open_sink(SinkKind::Sqlite, Some(path), format.clone(), false)?);
+    }
+    convert_csv_into(reader, record_filter.as_ref(), &mut tee)?;
+
+    Ok(())
+}
+
+/// Polls the latest data until SIGTERM or SIGINT, reporting each poll on the standard error.
+///
+/// With the `signals` feature, a signal stops the daemon after the current poll.
+///
+/// The outputs are reopened for every new file, CSV files are always appended to.
+fn run_daemon(
+    options: &Options,
+    record_filter: Option<&RecordFilter>,
+    format: &CsvFormat,
+) -> Result<(), Box<dyn Error>> {
+    let config = &options.config;
+    let output = config.output.clone().unwrap_or_default();
+    let mut outputs = vec![(SinkKind::Csv, output, format.clone())];
+    for tee in &config.tees {
+        outputs.push((tee.kind, tee.path.clone(), config.tee_format(tee)?));
+    }
+    #[cfg(feature = "sqlite")]
+    if let Some(path) = &config.database {
+        outputs.push((SinkKind::Sqlite, path.clone(), format.clone()));
+    }
+
+    let open_sink = || -> Result<Box<dyn RecordSink>, SinkError> {
+        let mut tee = Tee::new();
+        for (kind, path, format) in &outputs {
+            match kind {
+                #[cfg(feature = "sqlite")]
+                SinkKind::Sqlite => {
+                    tee.push(DbSink::new(Database::open(path, &format.field_config)?))
+                }
+                _ => tee.push(CsvAppender::open(path, format.clone())?),
+            }
+        }
+        Ok(Box::new(tee))
+    };
+
+    let mut daemon = Daemon::new(config.client());
+    #[cfg(feature = "signals")]
+    daemon.stop_on_signals()?;
+    daemon.run(open_sink, record_filter, |result| match result {
+        Ok(PollOutcome::Processed { file, records }) => {
+            eprintln!("processed {} with {} records", file, records)
+        }
+        Ok(PollOutcome::Unchanged { file }) => eprintln!("{} is unchanged", file),
+        Err(e) => print_error(e),
+    });
+
+    Ok(())
+}
+
+/// Downloads the archives for the stations and dates, reporting each one on the standard error.
+fn run_archives(options: &Options, dir: &str) -> Result<(), Box<dyn Error>> {
+    let config = &options.config;
+    let stations = parse_station_numbers(config.stations.as_deref().unwrap_or_default())?;
+    // Both are checked by `check_archives`, and the current time is always in range
+    let from = options.from.unwrap();
+    let to = match options.to {
+        Some(to) => to,
+        None => {
+            let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+            Timestamp::from_unix_time(now).unwrap()
+        }
+    };
+
+    std::fs::create_dir_all(dir)?;
+    let downloader = ArchiveDownloader::new(config.client(), config.dataset, dir);
+    let failures = downloader.download_range(&stations, from, to, |archive, result| match result {
+        Ok(ArchiveOutcome::Downloaded { path, resumed_from: 0 }) => {
+            eprintln!("downloaded {}", path.display())
+        }
+        Ok(ArchiveOutcome::Downloaded { path, resumed_from }) => {
+            eprintln!("downloaded {}, resumed from byte {}", path.display(), resumed_from)
+        }
+        Ok(ArchiveOutcome::Existing { path }) => eprintln!("{} is already present", path.display()),
+        Err(e) => {
+            eprintln!("failed to download {}", archive.name);
+            print_error(e);
+        }
+    })?;
+
+    match failures {
+        0 => Ok(()),
+        n => Err(format!("{} of the archives could not be downloaded", n).into()),
+    }
+}
+
+/// Prints an error with its chain of sources to the standard error.
+fn print_error(e: &dyn Error) {
+    eprintln!("error: {}", e);
+    let mut source = e.source();
+    while let Some(e) = source {
+        eprintln!("  caused by: {}", e);
+        source = e.source();
+    }
+}
+
+/// Opens an output file (or the standard output if there is no path), and the matching sink.
+///
+/// When appending to CSV, the existing header is checked and only new records are written.
+fn open_sink(
+    kind: SinkKind,
+    path: Option<&str>,
+    format: CsvFormat,
+    append: bool,
+) -> Result<Box<dyn RecordSink>, Box<dyn Error>> {
+    #[cfg(feature = "sqlite")]
+    if let (SinkKind::Sqlite, Some(path)) = (kind, path) {
+        return Ok(Box::new(DbSink::new(Database::open(path, &format.field_config)?)));
+    }
+
+    if let (SinkKind::Csv, Some(path), true) = (kind, path, append) {
+        return Ok(Box::new(CsvAppender::open(path, format)?));
+    }
+
+    let writer: Box<dyn Write> = match path {
+        None => Box::new(io::stdout().lock()),
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+    };
+    Ok(match kind {
+        SinkKind::Csv => Box::new(CsvSink::new(writer, format)?),
+        SinkKind::Sql(dialect) => Box::new(SqlSink::new(writer, &format.field_config, dialect)?),
+        SinkKind::Copy(copy_format) => {
+            Box::new(CopySink::new(writer, &format.field_config, copy_format)?)
+        }
+        #[cfg(feature = "sqlite")]
+        SinkKind::Sqlite => return Err("database output needs a file".into()),
+    })
+}
+
+fn open_input(input: &Input, client: &Client) -> Result<Box<dyn BufRead>, Box<dyn Error>> {
+    let reader: Box<dyn BufRead> = match input {
+        Input::Latest => return Ok(unzip(Cursor::new(client.download_latest()?))?),
+        Input::Url(url) => Box::new(Cursor::new(client.download_url(url)?)),
+        Input::Path(path) => Box::new(Cursor::new(client.download_with_path(path)?)),
+        Input::File { name, zip: true } if name == "-" => {
+            return Ok(unzip_stream(io::stdin().lock())?.1);
+        }
+        Input::File { name, zip: true } => {
+            return Ok(unzip_stream(BufReader::new(File::open(name)?))?.1);
+        }
+        Input::File { name, zip: false } if name == "-" => Box::new(io::stdin().lock()),
+        Input::File { name, zip: false } => Box::new(BufReader::new(File::open(name)?)),
+    };
+    unzip_if_needed(reader)
+}
+
+/// Checks for the zip signature at the start of the input, without consuming it.
+fn unzip_if_needed(mut reader: Box<dyn BufRead>) -> Result<Box<dyn BufRead>, Box<dyn Error>> {
+    if !reader.fill_buf()?.starts_with(b"PK\x03\x04") {
+        return Ok(reader);
+    }
+    Ok(unzip_stream(reader)?.1)
+}
+
+/// Prints the validation report, returning an error if there are any issues.
+fn validate(
+    reader: impl BufRead,
+    options: &Options,
+    record_filter: Option<&RecordFilter>,
+) -> Result<(), Box<dyn Error>> {
+    let report = validate_csv(reader, record_filter)?;
+    let output = match options.json_report {
+        true => format!("{}\n", report.to_json()),
+        false => report.to_string(),
+    };
+    match &options.config.output {
+        Some(path) => std::fs::write(path, output)?,
+        None => print!("{}", output),
+    }
+
+    match report.is_valid() {
+        true => Ok(()),
+        false => Err(format!("found {} issues in the input", report.issues.len()).into()),
+    }
+}
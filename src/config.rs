@@ -0,0 +1,536 @@
+/*
+    Created by Zoltan Kovari, 2025.
+
+    Licensed under the Apache License, Version 2.0
+    http://www.apache.org/licenses/LICENSE-2.0
+    (see LICENSE.txt)
+*/
+
+//! Settings read from a configuration file, in a simple INI-style format.
+//!
+//! The file consists of `[section]` headers and `key = value` lines, where keys belong to the
+//! last section. Empty lines and lines starting with `#` are ignored. Values are taken verbatim
+//! after trimming, without quoting, and an empty value means "not set" for the optional keys.
+//!
+//! ```text
+//! [download]
+//! base_url = https://odp.met.hu/climate/observations_hungary/
+//! cache = odp-cache
+//!
+//! [format]
+//! dataset = 10_minutes
+//! alignment = false
+//! delimiter = tab
+//! missing = null
+//!
+//! [filter]
+//! stations = 44527, Budapest%
+//! exclude = Q_*
+//!
+//! [output]
+//! path = latest.csv
+//! append = true
+//! tee = sql-postgres:latest.sql
+//! tee = csv,alignment=true,missing=-999:archive.csv
+//!
+//! [database]
+//! path = odp.sqlite
+//! ```
+//!
+//! The keys are the fields of `Config`, except for `output.path` (`Config::output`), `output.tee`
+//! (one per line, see `TeeConfig` for the syntax) and `database.path` (`Config::database`).
+
+use crate::download::{Client, DownloadCache, UreqTransport, ODP_ROOT};
+use crate::error::*;
+use crate::field::{Dataset, FieldConfig, FieldFilter};
+use crate::format::{CsvFormat, Delimiter, MissingValue};
+use crate::record::RecordFilter;
+use crate::sink::SinkKind;
+
+use std::error::Error;
+use std::fmt;
+use std::fmt::Display;
+use std::path::Path;
+use std::str::FromStr;
+
+#[cfg(test)]
+mod test;
+
+/// All the settings that can be given in a configuration file.
+///
+/// `Display` prints every setting in the file format, so the output can be parsed back.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Config {
+    /// Base URL of the server, see `Client`.
+    pub base_url: String,
+    /// Directory of the download cache, see `DownloadCache`.
+    pub cache: Option<String>,
+    /// Dataset of the input, the field lists and selection refer to its columns.
+    pub dataset: Dataset,
+    /// See `CsvFormat`.
+    pub alignment: bool,
+    pub delimiter: Delimiter,
+    pub missing: MissingValue,
+    /// Station list, see `RecordFilter::from_str`.
+    pub stations: Option<String>,
+    pub invert_stations: bool,
+    /// Field lists, see `FieldFilter::from_strs_for`.
+    pub include: Option<String>,
+    pub exclude: Option<String>,
+    /// Field type selection, see `FieldConfig::new_for`.
+    pub info: bool,
+    pub values: bool,
+    pub q: bool,
+    pub eor: bool,
+    /// Path of the output file, the standard output if not set.
+    pub output: Option<String>,
+    /// Appending to the output, see `CsvAppender`.
+    pub append: bool,
+    /// Additional outputs with their paths and formats.
+    pub tees: Vec<TeeConfig>,
+    /// Path of an SQLite database file to insert the records into.
+    pub database: Option<String>,
+}
+impl Default for Config {
+    /// The settings matching the defaults of the library (e.g. `CsvFormat::default`).
+    fn default() -> Self {
+        Self {
+            base_url: ODP_ROOT.to_string(),
+            cache: None,
+            dataset: Dataset::default(),
+            alignment: true,
+            delimiter: Delimiter::default(),
+            missing: MissingValue::default(),
+            stations: None,
+            invert_stations: false,
+            include: None,
+            exclude: None,
+            info: true,
+            values: true,
+            q: true,
+            eor: true,
+            output: None,
+            append: false,
+            tees: Vec::new(),
+            database: None,
+        }
+    }
+}
+impl Config {
+    /// Reads a configuration file, see `from_str`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|e| ConfigError::Io {
+            path: path.display().to_string(),
+            source: e,
+        })?;
+        Ok(Self::from_str(&content)?)
+    }
+
+    /// Returns a client for the base URL, with the cache if set.
+    pub fn client(&self) -> Client {
+        let client = Client::with_transport(&self.base_url, UreqTransport::new());
+        match &self.cache {
+            Some(dir) => client.with_cache(DownloadCache::new(dir)),
+            None => client,
+        }
+    }
+
+    /// Returns the format with the field selection.
+    pub fn csv_format(&self) -> Result<CsvFormat, ParseFieldFilterError> {
+        let (include, exclude) = (self.include.as_deref(), self.exclude.as_deref());
+        let field_filter = FieldFilter::from_strs_for(self.dataset, include, exclude)?;
+        Ok(CsvFormat {
+            alignment: self.alignment,
+            missing: self.missing,
+            delimiter: self.delimiter,
+            field_config: FieldConfig::new_for(
+                self.dataset,
+                self.info,
+                self.values,
+                self.q,
+                self.eor,
+                field_filter.as_ref(),
+            ),
+        })
+    }
+
+    /// Returns the format of a tee, the options it does not set are the same as in
+    /// `csv_format`.
+    pub fn tee_format(&self, tee: &TeeConfig) -> Result<CsvFormat, ParseFieldFilterError> {
+        let config = Self {
+            alignment: tee.alignment.unwrap_or(self.alignment),
+            delimiter: tee.delimiter.unwrap_or(self.delimiter),
+            missing: tee.missing.unwrap_or(self.missing),
+            include: tee.include.clone().or_else(|| self.include.clone()),
+            exclude: tee.exclude.clone().or_else(|| self.exclude.clone()),
+            info: tee.info.unwrap_or(self.info),
+            values: tee.values.unwrap_or(self.values),
+            q: tee.q.unwrap_or(self.q),
+            eor: tee.eor.unwrap_or(self.eor),
+            ..Self::default()
+        };
+        config.csv_format()
+    }
+
+    /// Returns the station filter, `None` if no stations are given.
+    pub fn record_filter(&self) -> Result<Option<RecordFilter<'_>>, ParseRecordFilterError> {
+        match &self.stations {
+            Some(s) => RecordFilter::from_str(s, self.invert_stations),
+            None => Ok(None),
+        }
+    }
+
+    /// Sets a single value, the filters are checked here so that errors have a line number.
+    ///
+    /// The field lists are only checked by `check_field_lists`, as they depend on the dataset.
+    fn set(&mut self, section: &str, key: &str, value: &str) -> Result<(), SetError> {
+        let optional = |value: &str| match value.is_empty() {
+            true => None,
+            false => Some(value.to_string()),
+        };
+
+        match (section, key) {
+            ("download", "base_url") if value.is_empty() => return Err("URL is empty".into()),
+            ("download", "base_url") => self.base_url = value.to_string(),
+            ("download", "cache") => self.cache = optional(value),
+            ("format", "dataset") => self.dataset = Dataset::try_from(value)?,
+            ("format", "alignment") => self.alignment = value.parse()?,
+            ("format", "delimiter") => self.delimiter = parse_delimiter(value)?,
+            ("format", "missing") => self.missing = parse_missing(value)?,
+            ("filter", "stations") => {
+                RecordFilter::from_str(value, false)?;
+                self.stations = optional(value);
+            }
+            ("filter", "invert_stations") => self.invert_stations = value.parse()?,
+            ("filter", "include") => self.include = optional(value),
+            ("filter", "exclude") => self.exclude = optional(value),
+            ("filter", "info") => self.info = value.parse()?,
+            ("filter", "values") => self.values = value.parse()?,
+            ("filter", "q") => self.q = value.parse()?,
+            ("filter", "eor") => self.eor = value.parse()?,
+            ("output", "path") => self.output = optional(value),
+            ("output", "append") => self.append = value.parse()?,
+            ("output", "tee") => self.tees.push(TeeConfig::from_str(value)?),
+            ("database", "path") => self.database = optional(value),
+            _ => return Err(SetError::UnknownKey),
+        }
+        Ok(())
+    }
+
+    /// Checks the field lists against the dataset, once all the values are set.
+    ///
+    /// `lines` has the line numbers of the settings: `include`, `exclude`, and the tees.
+    fn check_field_lists(&self, lines: &FieldListLines) -> Result<(), ParseConfigError> {
+        let check = |line: Option<usize>, key: &str, include, exclude| {
+            match FieldFilter::from_strs_for(self.dataset, include, exclude) {
+                Err(e) => Err(ParseConfigError::InvalidValue {
+                    line: line.unwrap_or_default(),
+                    key: key.to_string(),
+                    source: e.into(),
+                }),
+                Ok(_) => Ok(()),
+            }
+        };
+
+        check(lines.include, "filter.include", self.include.as_deref(), None)?;
+        check(lines.exclude, "filter.exclude", None, self.exclude.as_deref())?;
+        for (tee, line) in self.tees.iter().zip(&lines.tees) {
+            let (include, exclude) = (tee.include.as_deref(), tee.exclude.as_deref());
+            check(Some(*line), "output.tee", include, exclude)?;
+        }
+        Ok(())
+    }
+}
+impl FromStr for Config {
+    type Err = ParseConfigError;
+
+    /// Parses the content of a configuration file, the settings missing from it are left at
+    /// their defaults.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut config = Self::default();
+        let mut section = None;
+        let mut field_lists = FieldListLines::default();
+
+        for (i, line) in s.lines().enumerate() {
+            let line_number = i + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                let name = name.trim();
+                if !SECTIONS.contains(&name) {
+                    return Err(ParseConfigError::UnknownSection {
+                        line: line_number,
+                        name: name.to_string(),
+                    });
+                }
+                section = Some(name);
+                continue;
+            }
+
+            let (key, value) = match (section, line.split_once('=')) {
+                (Some(_), Some((key, value))) => (key.trim(), value.trim()),
+                _ => return Err(ParseConfigError::Syntax { line: line_number }),
+            };
+            let section = section.unwrap_or_default();
+            match config.set(section, key, value) {
+                Ok(()) => match (section, key) {
+                    ("filter", "include") => field_lists.include = Some(line_number),
+                    ("filter", "exclude") => field_lists.exclude = Some(line_number),
+                    ("output", "tee") => field_lists.tees.push(line_number),
+                    _ => {}
+                },
+                Err(SetError::UnknownKey) => {
+                    return Err(ParseConfigError::UnknownKey {
+                        line: line_number,
+                        key: format!("{}.{}", section, key),
+                    });
+                }
+                Err(SetError::InvalidValue(e)) => {
+                    return Err(ParseConfigError::InvalidValue {
+                        line: line_number,
+                        key: format!("{}.{}", section, key),
+                        source: e,
+                    });
+                }
+            }
+        }
+
+        // Checked at the end, as the dataset may be set after the lists
+        config.check_field_lists(&field_lists)?;
+        Ok(config)
+    }
+}
+impl Display for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let optional = |value: &Option<String>| value.clone().unwrap_or_default();
+        let delimiter = format_delimiter(self.delimiter);
+        let missing = format_missing(self.missing);
+
+        writeln!(f, "[download]")?;
+        entry(f, "base_url", &self.base_url)?;
+        entry(f, "cache", optional(&self.cache))?;
+        writeln!(f)?;
+        writeln!(f, "[format]")?;
+        entry(f, "dataset", self.dataset)?;
+        entry(f, "alignment", self.alignment)?;
+        entry(f, "delimiter", delimiter)?;
+        entry(f, "missing", missing)?;
+        writeln!(f)?;
+        writeln!(f, "[filter]")?;
+        entry(f, "stations", optional(&self.stations))?;
+        entry(f, "invert_stations", self.invert_stations)?;
+        entry(f, "include", optional(&self.include))?;
+        entry(f, "exclude", optional(&self.exclude))?;
+        entry(f, "info", self.info)?;
+        entry(f, "values", self.values)?;
+        entry(f, "q", self.q)?;
+        entry(f, "eor", self.eor)?;
+        writeln!(f)?;
+        writeln!(f, "[output]")?;
+        entry(f, "path", optional(&self.output))?;
+        entry(f, "append", self.append)?;
+        for tee in &self.tees {
+            entry(f, "tee", tee)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "[database]")?;
+        entry(f, "path", optional(&self.database))
+    }
+}
+
+/// An additional output, with the format options that differ from the main output.
+///
+/// The string representation is `KIND[,OPTION=VALUE...]:FILE`, e.g.
+/// `csv,alignment=true,include=t,ta:archive.csv`. The options are the keys of the `format` and
+/// `filter` sections that affect the format: `alignment`, `delimiter`, `missing`, `include`,
+/// `exclude`, `info`, `values`, `q` and `eor`. The field lists may contain commas, as an item
+/// without `=` continues the previous list.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TeeConfig {
+    pub kind: SinkKind,
+    pub path: String,
+    pub alignment: Option<bool>,
+    pub delimiter: Option<Delimiter>,
+    pub missing: Option<MissingValue>,
+    pub include: Option<String>,
+    pub exclude: Option<String>,
+    pub info: Option<bool>,
+    pub values: Option<bool>,
+    pub q: Option<bool>,
+    pub eor: Option<bool>,
+}
+impl TeeConfig {
+    /// Constructs an instance with the same format as the main output.
+    pub fn new(kind: SinkKind, path: impl Into<String>) -> Self {
+        Self {
+            kind,
+            path: path.into(),
+            alignment: None,
+            delimiter: None,
+            missing: None,
+            include: None,
+            exclude: None,
+            info: None,
+            values: None,
+            q: None,
+            eor: None,
+        }
+    }
+
+    fn set(&mut self, option: &str, value: &str) -> Result<(), ParseTeeError> {
+        let invalid = |e: BoxedError| ParseTeeError::InvalidValue {
+            option: option.to_string(),
+            source: e,
+        };
+        let flag = |value: &str| value.parse::<bool>().map_err(|e| invalid(e.into()));
+        match option {
+            "alignment" => self.alignment = Some(flag(value)?),
+            "delimiter" => self.delimiter = Some(parse_delimiter(value).map_err(invalid)?),
+            "missing" => self.missing = Some(parse_missing(value).map_err(invalid)?),
+            "include" => self.include = Some(value.to_string()),
+            "exclude" => self.exclude = Some(value.to_string()),
+            "info" => self.info = Some(flag(value)?),
+            "values" => self.values = Some(flag(value)?),
+            "q" => self.q = Some(flag(value)?),
+            "eor" => self.eor = Some(flag(value)?),
+            _ => return Err(ParseTeeError::UnknownOption(option.to_string())),
+        }
+        Ok(())
+    }
+}
+impl FromStr for TeeConfig {
+    type Err = ParseTeeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (spec, path) = match s.split_once(':') {
+            Some((spec, path)) if !path.is_empty() => (spec, path),
+            _ => return Err(ParseTeeError::Syntax(s.to_string())),
+        };
+        let mut items = spec.split(',');
+        let kind = SinkKind::try_from(items.next().unwrap_or_default().trim())?;
+
+        // Items without `=` are joined to the previous option, for the field lists
+        let mut options: Vec<(&str, String)> = Vec::new();
+        for item in items {
+            match (item.split_once('='), options.last_mut()) {
+                (Some((option, value)), _) => options.push((option.trim(), value.to_string())),
+                (None, Some((_, value))) => *value = format!("{},{}", value, item),
+                (None, None) => return Err(ParseTeeError::Syntax(s.to_string())),
+            }
+        }
+
+        let mut tee = Self::new(kind, path);
+        for (option, value) in options {
+            tee.set(option, value.trim())?;
+        }
+        Ok(tee)
+    }
+}
+impl Display for TeeConfig {
+    /// Prints the string representation, with the options that are set.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind)?;
+        let flags = [
+            ("alignment", self.alignment),
+            ("info", self.info),
+            ("values", self.values),
+            ("q", self.q),
+            ("eor", self.eor),
+        ];
+        for (option, value) in flags {
+            if let Some(value) = value {
+                write!(f, ",{}={}", option, value)?;
+            }
+        }
+        if let Some(delimiter) = self.delimiter {
+            write!(f, ",delimiter={}", format_delimiter(delimiter))?;
+        }
+        if let Some(missing) = self.missing {
+            write!(f, ",missing={}", format_missing(missing))?;
+        }
+        if let Some(include) = &self.include {
+            write!(f, ",include={}", include)?;
+        }
+        if let Some(exclude) = &self.exclude {
+            write!(f, ",exclude={}", exclude)?;
+        }
+        write!(f, ":{}", self.path)
+    }
+}
+
+/// Line numbers of the field list settings, see `Config::check_field_lists`.
+#[derive(Default)]
+struct FieldListLines {
+    include: Option<usize>,
+    exclude: Option<usize>,
+    tees: Vec<usize>,
+}
+
+/// Writes a `key = value` line, without trailing whitespace for empty values.
+fn entry(f: &mut fmt::Formatter<'_>, key: &str, value: impl Display) -> fmt::Result {
+    writeln!(f, "{}", format!("{} = {}", key, value).trim_end())
+}
+
+/// Names of the sections.
+const SECTIONS: [&str; 5] = ["download", "format", "filter", "output", "database"];
+
+/// Parses a delimiter given as a single character, or as "tab" (also `\t`) or "space", as in the
+/// configuration file and on the command line.
+pub fn parse_delimiter(s: &str) -> Result<Delimiter, Box<dyn Error + Send + Sync>> {
+    let ch = match s {
+        "tab" | "\\t" => '\t',
+        "space" => ' ',
+        _ => {
+            let mut chars = s.chars();
+            match (chars.next(), chars.next()) {
+                (Some(ch), None) => ch,
+                _ => return Err("expected a single character, \"tab\" or \"space\"".into()),
+            }
+        }
+    };
+    Ok(Delimiter::try_from(ch)?)
+}
+
+/// Inverse of `parse_delimiter`.
+pub fn format_delimiter(delimiter: Delimiter) -> String {
+    match *delimiter.as_ref() {
+        '\t' => "tab".to_string(),
+        ' ' => "space".to_string(),
+        ch => ch.to_string(),
+    }
+}
+
+/// Parses a missing value representation, or "empty", as in the configuration file and on the
+/// command line.
+pub fn parse_missing(s: &str) -> Result<MissingValue, Box<dyn Error + Send + Sync>> {
+    match s {
+        "empty" => Ok(MissingValue::Empty),
+        _ => Ok(MissingValue::try_from(s)?),
+    }
+}
+
+/// Inverse of `parse_missing`.
+pub fn format_missing(missing: MissingValue) -> String {
+    match missing {
+        MissingValue::Empty => "empty".to_string(),
+        missing => missing.to_string(),
+    }
+}
+
+/// Source of `ParseConfigError::InvalidValue`.
+type BoxedError = Box<dyn Error + Send + Sync>;
+
+/// Failure of `Config::set`.
+enum SetError {
+    UnknownKey,
+    InvalidValue(BoxedError),
+}
+impl<E: Into<BoxedError>> From<E> for SetError {
+    fn from(e: E) -> Self {
+        Self::InvalidValue(e.into())
+    }
+}
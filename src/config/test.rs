@@ -0,0 +1,151 @@
+/*
+    Created by Zoltan Kovari, 2025.
+
+    Licensed under the Apache License, Version 2.0
+    http://www.apache.org/licenses/LICENSE-2.0
+    (see LICENSE.txt)
+*/
+
+use super::*;
+
+use crate::copy::CopyFormat;
+use crate::field::{Dataset, Field};
+use crate::sql::Dialect;
+
+const TEST_CONFIG: &str = "\
+# Every 10 minutes
+[download]
+base_url = http://localhost:8080/odp
+cache = /var/cache/odp
+
+[format]
+alignment = false
+delimiter = tab
+missing = empty
+
+[filter]
+stations = 44527, Budapest%
+exclude = Q_*
+eor = false
+
+[output]
+path = latest.csv
+append = true
+tee = sql-postgres:latest.sql
+tee = copy-csv:latest.copy
+tee = csv,alignment=true,missing=-999,include=t,ta,exclude=Q_*:archive.csv
+";
+
+// Checking that the values are read, and the rest is left at the defaults.
+#[test]
+fn test_config_from_str() {
+    let config = Config::from_str(TEST_CONFIG).unwrap();
+    assert_eq!(config.base_url, "http://localhost:8080/odp");
+    let client = config.client();
+    assert_eq!(client.base_url(), "http://localhost:8080/odp/");
+    assert_eq!(client.cache().unwrap().dir(), std::path::Path::new("/var/cache/odp"));
+    assert!(Config::default().client().cache().is_none());
+    assert_eq!(config.stations.as_deref(), Some("44527, Budapest%"));
+    assert!(config.include.is_none());
+    assert!(config.append && config.info);
+    assert_eq!(config.tees[..2], [
+        TeeConfig::new(SinkKind::Sql(Dialect::Postgres), "latest.sql"),
+        TeeConfig::new(SinkKind::Copy(CopyFormat::Csv), "latest.copy"),
+    ]);
+    assert_eq!(config.tees[2].include.as_deref(), Some("t,ta"));
+    assert_eq!(config.tees[2].exclude.as_deref(), Some("Q_*"));
+    assert!(config.database.is_none());
+
+    let format = config.csv_format().unwrap();
+    assert_eq!(format.delimiter, Delimiter::try_from('\t').unwrap());
+    assert_eq!(format.missing, MissingValue::Empty);
+    let fields: Vec<Field> = format.field_config.fields().copied().collect();
+    assert_eq!(fields.len(), 30);
+    assert!(!fields.contains(&Field::EOR) && !fields.contains(&Field::Q_Temp));
+    assert!(config.record_filter().unwrap().is_some());
+
+    // The tees only override the options they set
+    let tee_format = config.tee_format(&config.tees[2]).unwrap();
+    assert!(tee_format.alignment);
+    assert_eq!(tee_format.delimiter, format.delimiter);
+    assert_eq!(tee_format.missing, MissingValue::Minus999);
+    let fields: Vec<Field> = tee_format.field_config.fields().copied().collect();
+    assert!(fields.contains(&Field::Temp) && !fields.contains(&Field::Q_Temp));
+    assert_eq!(config.tee_format(&config.tees[0]).unwrap(), format);
+
+    // The field lists refer to the columns of the dataset
+    let config = "[format]\ndataset = daily\n[filter]\ninclude = rs\nvalues = false\nq = false";
+    let format = Config::from_str(config).unwrap().csv_format().unwrap();
+    assert_eq!(format.field_config.dataset(), Dataset::Daily);
+    assert_eq!(format.field_config.fields().nth(6), Some(&Field::RainSum));
+    // Also if the dataset comes after them
+    let config = "[filter]\ninclude = rs\nvalues = false\nq = false\n[format]\ndataset = daily";
+    let format = Config::from_str(config).unwrap().csv_format().unwrap();
+    assert_eq!(format.field_config.fields().nth(6), Some(&Field::RainSum));
+
+    assert_eq!(Config::from_str("").unwrap(), Config::default());
+    assert_eq!(Config::default().csv_format().unwrap(), CsvFormat::default());
+}
+
+// Checking that the printed settings are parsed back to the same.
+#[test]
+fn test_config_display() {
+    let config = Config::from_str(TEST_CONFIG).unwrap();
+    assert_eq!(Config::from_str(&config.to_string()).unwrap(), config);
+    assert!(config.to_string().contains("\ndelimiter = tab\n"));
+
+    let config = Config::default();
+    assert_eq!(Config::from_str(&config.to_string()).unwrap(), config);
+}
+
+// Checking the errors and their line numbers.
+#[test]
+fn test_config_errors() {
+    let error = |s: &str| Config::from_str(s).unwrap_err();
+
+    assert!(matches!(error("base_url = x"), ParseConfigError::Syntax { line: 1 }));
+    assert!(matches!(error("[format]\n\nalignment"), ParseConfigError::Syntax { line: 3 }));
+    match error("[download]\n[input]") {
+        ParseConfigError::UnknownSection { line: 2, name } => assert_eq!(name, "input"),
+        e => panic!("unexpected error {:?}", e),
+    }
+    match error("[format]\nstations = 44527") {
+        ParseConfigError::UnknownKey { line: 2, key } => assert_eq!(key, "format.stations"),
+        e => panic!("unexpected error {:?}", e),
+    }
+    for (input, key) in [
+        ("[format]\nalignment = yes", "format.alignment"),
+        ("[format]\ndataset = monthly", "format.dataset"),
+        ("[format]\ndataset = daily\n[filter]\ninclude = ta..tx", "filter.include"),
+        ("[format]\ndelimiter = ab", "format.delimiter"),
+        ("[format]\nmissing = NaN", "format.missing"),
+        ("[filter]\nstations = 44527,,13704", "filter.stations"),
+        ("[filter]\ninclude = t,nonexistent", "filter.include"),
+        ("[output]\ntee = sql-mysql:x.sql", "output.tee"),
+        ("[output]\ntee = x.csv", "output.tee"),
+        ("[output]\ntee = csv,t:x.csv", "output.tee"),
+        ("[output]\ntee = csv,quote=true:x.csv", "output.tee"),
+        ("[output]\ntee = csv,missing=NaN:x.csv", "output.tee"),
+        ("[output]\ntee = csv,include=nonexistent:x.csv", "output.tee"),
+    ] {
+        match error(input) {
+            e @ ParseConfigError::InvalidValue { .. } => {
+                assert_eq!(e.line(), input.lines().count());
+                assert!(e.to_string().contains(key), "{}", e);
+                assert!(e.source().is_some());
+            }
+            e => panic!("unexpected error {:?} for {:?}", e, input),
+        }
+    }
+
+    // The field lists are checked at the end, with the line of the list
+    match error("[filter]\ninclude = ta..tx\n[format]\ndataset = daily") {
+        ParseConfigError::InvalidValue { line: 2, key, .. } => assert_eq!(key, "filter.include"),
+        e => panic!("unexpected error {:?}", e),
+    }
+
+    match Config::load("nonexistent.ini") {
+        Err(ConfigError::Io { path, .. }) => assert_eq!(path, "nonexistent.ini"),
+        other => panic!("unexpected result {:?}", other),
+    }
+}
@@ -0,0 +1,171 @@
+/*
+    Created by Zoltan Kovari, 2025.
+
+    Licensed under the Apache License, Version 2.0
+    http://www.apache.org/licenses/LICENSE-2.0
+    (see LICENSE.txt)
+*/
+
+//! Functions for exporting records in the PostgreSQL `COPY` format.
+//!
+//! The table definition is the same as in the SQL scripts, see `sql::create_table_sql` with
+//! `Dialect::Postgres`.
+
+use crate::error::*;
+use crate::field::{Field, FieldConfig};
+use crate::record::{MetRecord, MetValue};
+use crate::sql::{column_type, Dialect, TABLE_NAME};
+
+use std::borrow::Borrow;
+use std::fmt;
+use std::fmt::Display;
+use std::io::Write;
+
+#[cfg(test)]
+mod test;
+
+/// Representation of a missing value, in both formats.
+const NULL: &str = "\\N";
+
+/// Variants of the `COPY` format.
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub enum CopyFormat {
+    /// Tab-separated, special characters escaped with backslashes.
+    #[default]
+    Text,
+    /// Comma-separated, values quoted where necessary.
+    Csv,
+}
+impl Display for CopyFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", match self {
+            Self::Text => "text",
+            Self::Csv => "csv",
+        })
+    }
+}
+
+/// Returns the `COPY ... FROM STDIN` statement matching the output of `CopyWriter`.
+pub fn copy_statement(field_config: &FieldConfig, format: CopyFormat) -> String {
+    format!(
+        "COPY \"{}\" ({}) FROM STDIN WITH (FORMAT {}, NULL '{}');",
+        TABLE_NAME,
+        columns(field_config)
+            .map(|f| format!("\"{}\"", f.title()))
+            .collect::<Vec<String>>()
+            .join(", "),
+        format,
+        NULL,
+    )
+}
+
+/// Writes records as `COPY` data lines, one per record, terminated by LF.
+///
+/// Only the selected fields are written, except for `EOR` (as it has no column). Missing values
+/// are always written as `\N`, the `MissingValue` of a `CsvFormat` does not apply here.
+pub struct CopyWriter<W: Write> {
+    writer: W,
+    fields: Vec<Field>,
+    format: CopyFormat,
+    line: usize,
+}
+impl<W: Write> CopyWriter<W> {
+    /// Constructs a writer, nothing is written yet.
+    pub fn new(writer: W, field_config: &FieldConfig, format: CopyFormat) -> Self {
+        Self {
+            writer,
+            fields: columns(field_config).collect(),
+            format,
+            line: 0,
+        }
+    }
+
+    /// Writes a single record.
+    pub fn write(&mut self, record: &MetRecord) -> Result<(), WriteCopyError> {
+        self.line += 1;
+        let delim = match self.format {
+            CopyFormat::Text => "\t",
+            CopyFormat::Csv => ",",
+        };
+        let values: Vec<String> = self
+            .fields
+            .iter()
+            .map(|f| value(record.get(*f), self.format))
+            .collect();
+        writeln!(self.writer, "{}", values.join(delim)).map_err(|e| WriteCopyError {
+            line: self.line,
+            source: e,
+        })
+    }
+
+    /// Flushes and returns the writer.
+    pub fn finish(mut self) -> Result<W, WriteCopyError> {
+        match self.writer.flush() {
+            Ok(()) => Ok(self.writer),
+            Err(e) => Err(WriteCopyError {
+                line: self.line,
+                source: e,
+            }),
+        }
+    }
+}
+
+/// Convenience routine to write all records with `CopyWriter`.
+pub fn write_copy<R: Borrow<MetRecord>>(
+    records: impl IntoIterator<Item = R>,
+    writer: impl Write,
+    field_config: &FieldConfig,
+    format: CopyFormat,
+) -> Result<(), WriteCopyError> {
+    let mut copy = CopyWriter::new(writer, field_config, format);
+    for record in records {
+        copy.write(record.borrow())?;
+    }
+    copy.finish()?;
+
+    Ok(())
+}
+
+/// The selected fields that have a column.
+fn columns(field_config: &FieldConfig) -> impl Iterator<Item = Field> + '_ {
+    field_config
+        .fields()
+        .filter(|f| column_type(**f, Dialect::Postgres).is_some())
+        .copied()
+}
+
+/// Formats and escapes a value, non-finite numbers are written as missing.
+fn value(value: Option<MetValue>, format: CopyFormat) -> String {
+    let text = match value {
+        None => return NULL.to_string(),
+        Some(MetValue::Timestamp(ts)) => ts.to_iso_string(),
+        Some(MetValue::Integer(n)) => n.to_string(),
+        Some(MetValue::Float(x)) if x.is_finite() => x.to_string(),
+        Some(MetValue::Float(_)) => return NULL.to_string(),
+        Some(MetValue::Text(s)) => s.to_string(),
+    };
+
+    match format {
+        CopyFormat::Text => {
+            let mut escaped = String::with_capacity(text.len());
+            for ch in text.chars() {
+                match ch {
+                    '\\' => escaped.push_str("\\\\"),
+                    '\t' => escaped.push_str("\\t"),
+                    '\n' => escaped.push_str("\\n"),
+                    '\r' => escaped.push_str("\\r"),
+                    ch => escaped.push(ch),
+                }
+            }
+            escaped
+        }
+        CopyFormat::Csv => {
+            // A quoted `\N` is not taken as missing
+            let special = |ch| matches!(ch, ',' | '"' | '\n' | '\r');
+            match text.chars().any(special) || text == NULL {
+                true => format!("\"{}\"", text.replace('"', "\"\"")),
+                false => text,
+            }
+        }
+    }
+}
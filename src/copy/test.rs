@@ -0,0 +1,76 @@
+/*
+    Created by Zoltan Kovari, 2025.
+
+    Licensed under the Apache License, Version 2.0
+    http://www.apache.org/licenses/LICENSE-2.0
+    (see LICENSE.txt)
+*/
+
+use super::*;
+
+use crate::csv::read_csv;
+use crate::format::{CsvFormat, Delimiter};
+use crate::record::test::TEST_CSV;
+use crate::record::RawRecord;
+
+fn test_config() -> FieldConfig {
+    let header = "Time;StationNumber;StationName;t;Q_t;EOR";
+    FieldConfig::from_header(header, Delimiter::default()).unwrap()
+}
+
+fn test_records() -> Vec<MetRecord> {
+    let format = CsvFormat {
+        alignment: false,
+        field_config: test_config(),
+        ..Default::default()
+    };
+    [
+        "202501101350;44527;Budapest Pestszentlőrinc;4.3;;EOR",
+        "202501101350;13704;Sopron, \"Kuruc\"\\domb;-999;\\N;EOR",
+    ]
+    .map(|line| MetRecord::try_from(RawRecord::from_csv(line, &format).unwrap()).unwrap())
+    .to_vec()
+}
+
+// Checking the statement, EOR has no column.
+#[test]
+fn test_copy_statement() {
+    assert_eq!(
+        copy_statement(&test_config(), CopyFormat::Csv),
+        "COPY \"data\" (\"Time\", \"StationNumber\", \"StationName\", \"t\", \"Q_t\") \
+         FROM STDIN WITH (FORMAT csv, NULL '\\N');",
+    );
+}
+
+// Checking the escaping and missing values in both formats.
+#[test]
+fn test_write_copy() {
+    let mut output = Vec::new();
+    write_copy(test_records(), &mut output, &test_config(), CopyFormat::Text).unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "\
+2025-01-10 13:50:00\t44527\tBudapest Pestszentlőrinc\t4.3\t\\N
+2025-01-10 13:50:00\t13704\tSopron, \"Kuruc\"\\\\domb\t\\N\t\\\\N
+",
+    );
+
+    let mut output = Vec::new();
+    write_copy(test_records(), &mut output, &test_config(), CopyFormat::Csv).unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "\
+2025-01-10 13:50:00,44527,Budapest Pestszentlőrinc,4.3,\\N
+2025-01-10 13:50:00,13704,\"Sopron, \"\"Kuruc\"\"\\domb\",\\N,\"\\N\"
+",
+    );
+
+    // Every record of the sample file is written on its own line
+    let (records, format) = read_csv(TEST_CSV.as_bytes(), None).unwrap();
+    let records = records.map(|r| MetRecord::try_from(r.unwrap()).unwrap());
+    let mut output = Vec::new();
+    write_copy(records, &mut output, &format.field_config, CopyFormat::Text).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert_eq!(output.lines().count(), TEST_CSV.lines().count() - 1);
+    assert!(output.lines().all(|l| l.split('\t').count() == 54));
+}
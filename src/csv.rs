@@ -9,51 +9,432 @@
 //! Functions for manipulating CSV data.
 
 use crate::error::*;
+use crate::field::Field;
 use crate::format::*;
 use crate::record::*;
+use crate::sink::*;
+use crate::validation::*;
 
-use std::io::{BufRead, Write};
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+#[cfg(test)]
+mod test;
 
 /// Verifies if every line in the input is valid by trying to convert it to `MetRecord` (thus
 /// looking for parsing errors).
 ///
 /// If a `RecordFilter` is supplied, only basic checks are run for those lines that do not
 /// satisfy the filter, and the conversion step will be skipped.
+///
+/// Besides the conversion, the lines are checked against the format detected from the header
+/// (field count and padding), and for duplicate `Time` and `StationNumber` pairs. Every problem
+/// is collected in the returned report, errors are only returned if the header is invalid or
+/// the input cannot be read.
 pub fn validate_csv(
-    _reader: &impl BufRead,
-    _record_filter: Option<&RecordFilter>,
-) -> Result<(), ReadCsvError> {
-    todo!()
+    mut reader: impl BufRead,
+    record_filter: Option<&RecordFilter>,
+) -> Result<ValidationReport, ReadCsvError> {
+    let (format, _) = read_header(&mut reader)?;
+    let delim = *format.delimiter.as_ref();
+    let mut report = ValidationReport::new(format.clone());
+    let mut keys: HashSet<(String, String)> = HashSet::new();
+
+    let mut line = String::new();
+    let mut line_number = 1;
+    loop {
+        line.clear();
+        let read = reader.read_line(&mut line).map_err(|e| ReadCsvError::Io {
+            line: line_number + 1,
+            source: e,
+        })?;
+        if read == 0 {
+            break;
+        }
+        line_number += 1;
+        let text = line.trim_end_matches(['\r', '\n']);
+        if text.is_empty() {
+            continue;
+        }
+        report.lines += 1;
+
+        let mut add_issue = |station: Option<&str>, field, kind, message| {
+            report.issues.push(Issue {
+                line: line_number,
+                station: station.map(str::to_string),
+                field,
+                kind,
+                message,
+            })
+        };
+
+        let record = match RawRecord::from_csv(text, &format) {
+            Ok(rec) => rec,
+            Err(e) => {
+                let kind = match e {
+                    ParseRecordError::FieldCount { .. } => IssueKind::FieldCount,
+                    ParseRecordError::Misaligned { .. } => IssueKind::Misaligned,
+                    ParseRecordError::MissingMandatory { .. } => IssueKind::MissingMandatory,
+                };
+                let station = text
+                    .split(delim)
+                    .nth(1)
+                    .map(str::trim)
+                    .filter(|s| MissingValue::try_from(*s).is_err());
+                add_issue(station, e.field(), kind, e.to_string());
+                continue;
+            }
+        };
+        let station = record.get(Field::StationNumber).unwrap_or_default();
+
+        if let Some(filter) = record_filter {
+            if !filter.filter(&record) {
+                report.filtered += 1;
+                continue;
+            }
+        }
+
+        // Aligned lines with whitespace delimiters are already sliced by the widths
+        if !(format.alignment && delim.is_ascii_whitespace()) {
+            for (field, token) in format.field_config.fields().zip(text.split(delim)) {
+                let width = token.chars().count();
+                if format.alignment && width != usize::from(field.width()) {
+                    let msg = format!("expected width {}, found {}", field.width(), width);
+                    add_issue(Some(station), Some(*field), IssueKind::Misaligned, msg);
+                    break;
+                } else if !format.alignment && token.trim() != token {
+                    let msg = "value is padded with whitespace".to_string();
+                    add_issue(Some(station), Some(*field), IssueKind::Padded, msg);
+                    break;
+                }
+            }
+        }
+
+        if let Err(e) = MetRecord::try_from(&record) {
+            let msg = format!("invalid value \"{}\", {}", e.value(), e.reason());
+            add_issue(Some(station), Some(e.field()), IssueKind::InvalidValue, msg);
+        }
+
+        let time = record.get(Field::Time).unwrap_or_default();
+        if !keys.insert((time.to_string(), station.to_string())) {
+            let msg = format!("duplicate record for time {}", time);
+            add_issue(Some(station), None, IssueKind::Duplicate, msg);
+        }
+    }
+
+    Ok(report)
 }
 
-/// Converts each line in the input to a `RawRecord`.
+/// Reads the header line and prepares to convert each subsequent line to a `RawRecord`.
 ///
-/// As an auxiliary output, returns the detected format definition as well.
-pub fn read_csv<T>(
-    _reader: &impl BufRead,
-    _record_filter: Option<&RecordFilter>,
-) -> (T, CsvFormat)
-where
-    T: Iterator<Item = Result<RawRecord, ReadCsvError>>,
-{
-    todo!()
+/// The format is detected from the header by `CsvFormat::from_str`, and returned as an auxiliary
+/// output. The records are read lazily by the returned iterator, so the input does not have to
+/// fit in memory. Empty lines are skipped, and so are those that do not satisfy the
+/// `RecordFilter`, if supplied.
+pub fn read_csv<'f, R: BufRead>(
+    mut reader: R,
+    record_filter: Option<&'f RecordFilter<'f>>,
+) -> Result<(CsvRecords<'f, R>, CsvFormat), ReadCsvError> {
+    let (format, offset) = read_header(&mut reader)?;
+    let records = CsvRecords {
+        reader,
+        format: format.clone(),
+        record_filter,
+        line: String::new(),
+        line_number: 1,
+        offset,
+    };
+    Ok((records, format))
+}
+
+/// Reads the first line and detects the format from it, a byte order mark is ignored.
+///
+/// The length of the line in bytes is also returned, which is the offset of the next one.
+fn read_header(reader: &mut impl BufRead) -> Result<(CsvFormat, u64), ReadCsvError> {
+    let mut header = String::new();
+    let read = reader
+        .read_line(&mut header)
+        .map_err(|e| ReadCsvError::Io { line: 1, source: e })?;
+    if read == 0 {
+        return Err(ReadCsvError::EmptyInput);
+    }
+    let header = header
+        .trim_start_matches('\u{feff}')
+        .trim_end_matches(['\r', '\n']);
+    let format = CsvFormat::from_str(header).map_err(ReadCsvError::Header)?;
+    Ok((format, read as u64))
+}
+
+/// Iterator over the records of a CSV input, see `read_csv`.
+pub struct CsvRecords<'f, R> {
+    reader: R,
+    format: CsvFormat,
+    record_filter: Option<&'f RecordFilter<'f>>,
+    line: String,
+    line_number: usize,
+    /// Byte offset of the next line.
+    offset: u64,
+}
+impl<R> CsvRecords<'_, R> {
+    /// Returns the format detected from the header.
+    pub fn format(&self) -> &CsvFormat {
+        &self.format
+    }
+
+    /// Number of the last line read, starting from 1 (which is the header).
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+}
+impl<R: BufRead> Iterator for CsvRecords<'_, R> {
+    type Item = Result<RawRecord, ReadCsvError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.line.clear();
+            let offset = self.offset;
+            match self.reader.read_line(&mut self.line) {
+                Ok(0) => return None,
+                Ok(read) => {
+                    self.line_number += 1;
+                    self.offset += read as u64;
+                }
+                Err(e) => {
+                    return Some(Err(ReadCsvError::Io {
+                        line: self.line_number + 1,
+                        source: e,
+                    }));
+                }
+            }
+
+            if self.line.trim_end_matches(['\r', '\n']).is_empty() {
+                continue;
+            }
+
+            let record = match RawRecord::from_csv(&self.line, &self.format) {
+                Ok(rec) => rec,
+                Err(e) => {
+                    return Some(Err(ReadCsvError::Record {
+                        line: self.line_number,
+                        offset,
+                        source: e,
+                    }));
+                }
+            };
+            match self.record_filter {
+                Some(filter) if !filter.filter(&record) => continue,
+                _ => return Some(Ok(record)),
+            }
+        }
+    }
 }
 
 /// Tries to write out the supplied records with the format specified.
+///
+/// The header is written first (see the `Display` implementation of `CsvFormat`), then each
+/// record is projected onto the `FieldConfig` of the format (see `RawRecord::to_csv`). Lines are
+/// terminated by CRLF, the same as in the ODP files.
+///
+/// Every line is written separately, so the writer should be buffered for best performance.
 pub fn write_csv(
-    _records: impl IntoIterator<Item = RawRecord>,
-    _writer: &impl Write,
-    _format: &CsvFormat,
+    records: impl IntoIterator<Item = RawRecord>,
+    mut writer: impl Write,
+    format: &CsvFormat,
 ) -> Result<(), WriteCsvError> {
-    todo!()
+    write_header(&mut writer, format)?;
+    let mut line = 1;
+    for record in records {
+        line += 1;
+        write_record(&mut writer, &record, format, line)?;
+    }
+    flush(&mut writer, line)?;
+
+    Ok(())
 }
 
 /// Convenience routine to combine `read_csv` and `write_csv`.
+///
+/// Records are written as soon as they are read, so the input does not have to fit in memory.
 pub fn convert_csv(
-    _reader: &impl BufRead,
-    _writer: &impl Write,
-    _record_filter: Option<&RecordFilter>,
-    _format: &CsvFormat,
+    reader: impl BufRead,
+    mut writer: impl Write,
+    record_filter: Option<&RecordFilter>,
+    format: &CsvFormat,
 ) -> Result<(), CsvError> {
-    todo!()
+    let (records, _) = read_csv(reader, record_filter)?;
+
+    write_header(&mut writer, format)?;
+    let mut line = 1;
+    for record in records {
+        let record = record?;
+        line += 1;
+        write_record(&mut writer, &record, format, line)?;
+    }
+    flush(&mut writer, line)?;
+
+    Ok(())
+}
+
+/// Reads the input like `read_csv`, passes each record to the sink (e.g. a `Tee` of several
+/// outputs), then finishes it.
+///
+/// The input is read and parsed only once, no matter how many outputs there are. Returns the
+/// number of records written.
+pub fn convert_csv_into(
+    reader: impl BufRead,
+    record_filter: Option<&RecordFilter>,
+    sink: &mut dyn RecordSink,
+) -> Result<usize, SinkError> {
+    let (mut records, _) = read_csv(reader, record_filter)?;
+
+    let mut count = 0;
+    while let Some(record) = records.next() {
+        sink.write(&SinkRecord::new(record?).at_line(records.line_number()))?;
+        count += 1;
+    }
+    sink.finish()?;
+
+    Ok(count)
+}
+
+/// Appends records to existing CSV content, skipping those that are already present.
+///
+/// On construction, the header of the existing content is checked against the target format
+/// (alignment, delimiter and fields), and the `Time` and `StationNumber` pairs of its records are
+/// indexed. Records with a known pair are skipped by `write`, including repeated ones among the
+/// appended records. If the content is empty, the header is written first, so a single file can
+/// be grown run after run.
+pub struct CsvAppender<F: Write> {
+    writer: BufWriter<F>,
+    format: CsvFormat,
+    keys: HashSet<(String, String)>,
+    /// Number of the last line written (or read).
+    line: usize,
+    appended: usize,
+}
+impl CsvAppender<File> {
+    /// Opens (or creates) the file at `path` for appending, see `new`.
+    pub fn open(path: impl AsRef<Path>, format: CsvFormat) -> Result<Self, AppendCsvError> {
+        let file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)?;
+        Self::new(file, format)
+    }
+}
+impl<F: Read + Write + Seek> CsvAppender<F> {
+    /// Reads and indexes the existing content from the start, then prepares to write at the end.
+    ///
+    /// A missing line terminator at the end of the content is added before any record.
+    pub fn new(mut file: F, format: CsvFormat) -> Result<Self, AppendCsvError> {
+        file.rewind()?;
+        let mut keys = HashSet::new();
+        let mut line = 0;
+
+        let (records, found) = match read_csv(BufReader::new(&mut file), None) {
+            Ok(result) => (Some(result.0), Some(result.1)),
+            Err(ReadCsvError::EmptyInput) => (None, None),
+            Err(e) => return Err(e.into()),
+        };
+        if let (Some(records), Some(found)) = (records, found) {
+            if found.alignment != format.alignment
+                || found.delimiter != format.delimiter
+                || found.field_config != format.field_config
+            {
+                return Err(AppendCsvError::HeaderMismatch {
+                    expected: format.to_string(),
+                    found: found.to_string(),
+                });
+            }
+            line = 1;
+            for record in records {
+                let record = record?;
+                keys.insert(key(&record));
+                line += 1;
+            }
+        }
+
+        let end = file.seek(SeekFrom::End(0))?;
+        let mut writer = BufWriter::new(file);
+        if line == 0 {
+            write_header(&mut writer, &format).map_err(|e| e.source)?;
+            line = 1;
+        } else if end > 0 && !ends_with_newline(writer.get_mut(), end)? {
+            writer.write_all(LINE_END.as_bytes())?;
+        }
+
+        Ok(Self {
+            writer,
+            format,
+            keys,
+            line,
+            appended: 0,
+        })
+    }
+}
+impl<F: Write> CsvAppender<F> {
+    /// Writes the record if its `Time` and `StationNumber` pair is new, returns whether it was
+    /// written.
+    pub fn write(&mut self, record: &RawRecord) -> Result<bool, WriteCsvError> {
+        if !self.keys.insert(key(record)) {
+            return Ok(false);
+        }
+        self.line += 1;
+        write_record(&mut self.writer, record, &self.format, self.line)?;
+        self.appended += 1;
+        Ok(true)
+    }
+
+    /// Flushes the buffered lines.
+    pub fn flush(&mut self) -> Result<(), WriteCsvError> {
+        flush(&mut self.writer, self.line)
+    }
+
+    /// Number of records written so far (not counting the skipped ones).
+    pub fn appended(&self) -> usize {
+        self.appended
+    }
+}
+
+/// Key of a record for detecting duplicates, the same as in `validate_csv`.
+fn key(record: &RawRecord) -> (String, String) {
+    let get = |field| record.get(field).unwrap_or_default().trim().to_string();
+    (get(Field::Time), get(Field::StationNumber))
+}
+
+/// Checks the last byte of the content, `end` is its length.
+fn ends_with_newline(file: &mut (impl Read + Seek), end: u64) -> Result<bool, std::io::Error> {
+    let mut last = [0];
+    file.seek(SeekFrom::Start(end - 1))?;
+    file.read_exact(&mut last)?;
+    Ok(last[0] == b'\n')
+}
+
+/// Line terminator used for writing.
+const LINE_END: &str = "\r\n";
+
+pub(crate) fn write_header(
+    writer: &mut impl Write,
+    format: &CsvFormat,
+) -> Result<(), WriteCsvError> {
+    write!(writer, "{}{}", format, LINE_END).map_err(|e| WriteCsvError { line: 1, source: e })
+}
+
+pub(crate) fn write_record(
+    writer: &mut impl Write,
+    record: &RawRecord,
+    format: &CsvFormat,
+    line: usize,
+) -> Result<(), WriteCsvError> {
+    write!(writer, "{}{}", record.to_csv(format), LINE_END)
+        .map_err(|e| WriteCsvError { line, source: e })
+}
+
+/// Flushes the writer, `line` is the number of the last line written.
+fn flush(writer: &mut impl Write, line: usize) -> Result<(), WriteCsvError> {
+    writer.flush().map_err(|e| WriteCsvError { line, source: e })
 }
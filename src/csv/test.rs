@@ -0,0 +1,315 @@
+/*
+    Created by Zoltan Kovari, 2025.
+
+    Licensed under the Apache License, Version 2.0
+    http://www.apache.org/licenses/LICENSE-2.0
+    (see LICENSE.txt)
+*/
+
+use super::*;
+
+use crate::field::{Dataset, Field, FieldConfig, FieldFilter};
+use crate::record::test::TEST_CSV;
+use crate::record::{MetRecord, MetValue};
+use crate::timestamp::Timestamp;
+
+use std::error::Error;
+
+// Checking that the sample file is read completely with the default format detected.
+#[test]
+fn test_read_csv() {
+    let (records, format) = read_csv(TEST_CSV.as_bytes(), None).unwrap();
+    assert_eq!(format, CsvFormat::default());
+    assert_eq!(records.format(), &format);
+
+    let records: Vec<RawRecord> = records.map(|r| r.unwrap()).collect();
+    assert_eq!(records.len(), TEST_CSV.lines().count() - 1);
+    assert_eq!(records[0].get(Field::StationNumber), Some("38317"));
+    assert_eq!(records[records.len() - 1].get(Field::Time), Some("202501101400"));
+
+    let filter = RecordFilter::from_str("44527", false).unwrap();
+    let (records, _) = read_csv(TEST_CSV.as_bytes(), filter.as_ref()).unwrap();
+    assert_eq!(records.count(), 2);
+}
+
+// Checking that records are read lazily, and errors are reported with line numbers.
+#[test]
+fn test_read_csv_errors() {
+    let input = "Time;StationNumber;t;EOR\n\n202501101350;44527;4.3;EOR\n202501101350;56312;EOR\n";
+    let (mut records, _) = read_csv(input.as_bytes(), None).unwrap();
+    assert!(matches!(records.next(), Some(Ok(_))));
+    assert!(matches!(
+        records.next(),
+        Some(Err(ReadCsvError::Record {
+            line: 4,
+            offset: 53,
+            source: ParseRecordError::FieldCount {
+                expected: 4,
+                found: 3,
+                column: 23,
+            },
+        }))
+    ));
+    assert!(records.next().is_none());
+
+    assert!(matches!(
+        read_csv("".as_bytes(), None),
+        Err(ReadCsvError::EmptyInput)
+    ));
+    assert!(matches!(
+        read_csv("Time;t;EOR\n".as_bytes(), None),
+        Err(ReadCsvError::Header(ParseHeaderError::MissingStationNumber))
+    ));
+    assert!(matches!(
+        read_csv(&b"Time;StationNumber\n\xff\n"[..], None).unwrap().0.next(),
+        Some(Err(ReadCsvError::Io { line: 2, .. }))
+    ));
+}
+
+// Checking that the sample file is written back unchanged in the default format.
+#[test]
+fn test_write_csv() {
+    let (records, format) = read_csv(TEST_CSV.as_bytes(), None).unwrap();
+    let records: Vec<RawRecord> = records.map(|r| r.unwrap()).collect();
+
+    let mut output: Vec<u8> = Vec::new();
+    write_csv(records, &mut output, &format).unwrap();
+    assert_eq!(String::from_utf8(output).unwrap(), TEST_CSV);
+}
+
+// Checking conversion to a condensed format with a column subset.
+#[test]
+fn test_convert_csv() {
+    let ffilter = FieldFilter::new([Field::Temp, Field::GammaRad], std::iter::empty());
+    let format = CsvFormat {
+        alignment: false,
+        missing: MissingValue::Empty,
+        delimiter: Delimiter::try_from(',').unwrap(),
+        field_config: FieldConfig::new(false, false, false, false, ffilter.as_ref()),
+    };
+
+    let mut output: Vec<u8> = Vec::new();
+    convert_csv(TEST_CSV.as_bytes(), &mut output, None, &format).unwrap();
+    let output = String::from_utf8(output).unwrap();
+
+    let mut lines = output.split_terminator("\r\n");
+    assert_eq!(lines.next(), Some("Time,StationNumber,t,sg"));
+    assert_eq!(lines.next(), Some("202501101350,38317,,108.00"));
+    assert_eq!(lines.next(), Some("202501101350,44527,4.3,"));
+    assert_eq!(lines.count(), TEST_CSV.lines().count() - 3);
+
+    // Reading back the output gives the same format
+    let (mut records, read_format) = read_csv(output.as_bytes(), None).unwrap();
+    assert_eq!(read_format, format);
+    assert!(records.all(|r| r.is_ok()));
+
+    // Errors come through from reading
+    let input = "Time;StationNumber;t;EOR\n202501101350;44527;4.3\n";
+    assert!(matches!(
+        convert_csv(input.as_bytes(), &mut Vec::new(), None, &format),
+        Err(CsvError::ReadErr(ReadCsvError::Record { line: 2, .. }))
+    ));
+}
+
+// Checking that a daily file is read with its own columns, and converted the same way.
+#[test]
+fn test_convert_csv_daily() {
+    let input = "\
+Time;StationNumber;StationName;Latitude;Longitude;Elevation;t;Q_t;tn;Q_tn;tx;Q_tx;rs;Q_rs;sd;Q_sd;\
+ss;Q_ss;p;Q_p;u;Q_u;fs;Q_fs;fx;Q_fx;tsn;Q_tsn;EOR\r
+20250110;44527;Budapest Pestszentlőrinc;47.4291;19.1822;138.1;3.1;;-0.2;;5.9;;1.4;;0;;2.3;;\
+1012.4;;88;;2.1;;9.8;;-1.5;;EOR\r
+";
+    let (mut records, format) = read_csv(input.as_bytes(), None).unwrap();
+    assert_eq!(format.field_config.dataset(), Dataset::Daily);
+    let met = MetRecord::try_from(records.next().unwrap().unwrap()).unwrap();
+    let time = Timestamp::new(2025, 1, 10, 0, 0).unwrap();
+    assert_eq!(met.get(Field::Time), Some(MetValue::Timestamp(time)));
+    assert_eq!(met.get(Field::RainSum), Some(MetValue::Float(1.4)));
+    assert_eq!(met.get(Field::Q_RainSum), None);
+
+    let ffilter = FieldFilter::from_strs_for(Dataset::Daily, Some("rs..sd"), None).unwrap();
+    let field_config =
+        FieldConfig::new_for(Dataset::Daily, false, false, false, false, ffilter.as_ref());
+    let format = CsvFormat {
+        field_config,
+        ..CsvFormat::default()
+    };
+    let mut output: Vec<u8> = Vec::new();
+    convert_csv(input.as_bytes(), &mut output, None, &format).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    let mut lines = output.split_terminator("\r\n");
+    assert_eq!(lines.next().map(str::trim), Some("Time;StationNumber;   rs;Q_rs;  sd"));
+    assert_eq!(lines.next().map(|l| l.replace(' ', "")), Some("20250110;44527;1.4;;0".into()));
+}
+
+// Checking that write errors carry the line number, and the sources are chained.
+#[test]
+fn test_csv_error_context() {
+    /// Writer that fails after a number of bytes.
+    struct Limited(usize);
+    impl Write for Limited {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            match self.0 {
+                0 => Err(std::io::Error::other("disk full")),
+                n => {
+                    self.0 = n.saturating_sub(buf.len());
+                    Ok(buf.len().min(n))
+                }
+            }
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let format = CsvFormat::default();
+    let header_len = format.to_string().len() + 2;
+    let result = convert_csv(TEST_CSV.as_bytes(), Limited(header_len), None, &format);
+    match result {
+        Err(CsvError::WriteErr(e)) => {
+            assert_eq!(e.line(), 2);
+            assert_eq!(e.to_string(), "failed to write CSV at line 2");
+            assert_eq!(e.source().unwrap().to_string(), "disk full");
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+
+    let input = "Time;StationNumber;t;EOR\n202501101350;;4.3;EOR\n";
+    let e = convert_csv(input.as_bytes(), Vec::new(), None, &format).unwrap_err();
+    assert_eq!(e.to_string(), "failed to read CSV at line 2 (byte 25)");
+    assert_eq!(
+        e.source().unwrap().to_string(),
+        "failed to parse record, missing value for `StationNumber` at column 14",
+    );
+}
+
+// Checking that the sample file is valid.
+#[test]
+fn test_validate_csv() {
+    let report = validate_csv(TEST_CSV.as_bytes(), None).unwrap();
+    assert!(report.is_valid());
+    assert_eq!(report.lines, TEST_CSV.lines().count() - 1);
+    assert_eq!(report.filtered, 0);
+    assert_eq!(report.invalid_lines(), 0);
+
+    let filter = RecordFilter::from_str("44527", false).unwrap();
+    let report = validate_csv(TEST_CSV.as_bytes(), filter.as_ref()).unwrap();
+    assert_eq!(report.filtered, report.lines - 2);
+}
+
+// Checking the issues found in an invalid input, and the report outputs.
+#[test]
+fn test_validate_csv_issues() {
+    let input = "\
+Time;StationNumber;t;EOR\r
+202501101350;44527;4.3;EOR\r
+202501101350;44527;4.4;EOR\r
+202501101350;56312;4.2\r
+202501101350;-999;4.2;EOR\r
+202501101350;57202;x;EOR\r
+202501101350; 38317;4.0;EOR\r
+2025011013;12345;4.0;EOR\r
+";
+    let report = validate_csv(input.as_bytes(), None).unwrap();
+    assert_eq!(report.lines, 7);
+    assert_eq!(report.invalid_lines(), 6);
+
+    let issues: Vec<(usize, Option<&str>, Option<Field>, IssueKind)> = report
+        .issues
+        .iter()
+        .map(|i| (i.line, i.station.as_deref(), i.field, i.kind))
+        .collect();
+    assert_eq!(
+        issues,
+        [
+            (3, Some("44527"), None, IssueKind::Duplicate),
+            (4, Some("56312"), None, IssueKind::FieldCount),
+            (5, None, Some(Field::StationNumber), IssueKind::MissingMandatory),
+            (6, Some("57202"), Some(Field::Temp), IssueKind::InvalidValue),
+            (7, Some("38317"), Some(Field::StationNumber), IssueKind::Padded),
+            (8, Some("12345"), Some(Field::Time), IssueKind::InvalidValue),
+        ],
+    );
+    assert_eq!(report.counts()[&IssueKind::InvalidValue], 2);
+
+    // Records that do not satisfy the filter are not converted
+    let filter = RecordFilter::from_str("44527", false).unwrap();
+    let filtered = validate_csv(input.as_bytes(), filter.as_ref()).unwrap();
+    assert_eq!(filtered.filtered, 3);
+    assert_eq!(filtered.invalid_lines(), 3);
+
+    let text = report.to_string();
+    assert!(text.contains("Lines: 7, valid: 1, invalid: 6, filtered out: 0"));
+    assert!(text.contains("  line 6, station 57202, field `t`: invalid value \"x\""));
+
+    let json = report.to_json();
+    assert!(json.starts_with(
+        "{\"format\":{\"alignment\":false,\"delimiter\":\";\",\"fields\":[\"Time\",\"StationNumber\",\"t\",\"EOR\"]},\
+        \"summary\":{\"lines\":7,\"valid\":1,\"invalid\":6,\"filtered\":0},\
+        \"counts\":{\"field_count\":1,\"missing_mandatory\":1,\"padded\":1,\"invalid_value\":2,\"duplicate\":1},\
+        \"issues\":[{\"line\":3,\"station\":\"44527\",\"field\":null,\"kind\":\"duplicate\","
+    ));
+    assert!(json.contains("\"message\":\"invalid value \\\"x\\\", invalid float literal\""));
+
+    // An aligned header expects padding
+    let input = "        Time;StationNumber;    t;EOR\r\n202501101350;44527;  4.3;EOR\r\n";
+    let report = validate_csv(input.as_bytes(), None).unwrap();
+    assert_eq!(report.issues[0].kind, IssueKind::Misaligned);
+    assert_eq!(report.issues[0].field, Some(Field::StationNumber));
+}
+
+// Checking that appending twice to the same content gives the input once, with one header.
+#[test]
+fn test_csv_appender() {
+    let (records, _) = read_csv(TEST_CSV.as_bytes(), None).unwrap();
+    let records: Vec<RawRecord> = records.map(|r| r.unwrap()).collect();
+    let (first, second) = records.split_at(100);
+
+    let mut file = std::io::Cursor::new(Vec::new());
+    let mut appender = CsvAppender::new(&mut file, CsvFormat::default()).unwrap();
+    assert!(first.iter().all(|r| appender.write(r).unwrap()));
+    appender.flush().unwrap();
+    drop(appender);
+
+    let mut appender = CsvAppender::new(&mut file, CsvFormat::default()).unwrap();
+    for record in &records {
+        appender.write(record).unwrap();
+    }
+    assert!(!appender.write(&second[0]).unwrap());
+    assert_eq!(appender.appended(), second.len());
+    appender.flush().unwrap();
+    drop(appender);
+    assert_eq!(String::from_utf8(file.into_inner()).unwrap(), TEST_CSV);
+}
+
+// Checking the header comparison, and the content without a final line terminator.
+#[test]
+fn test_csv_appender_existing() {
+    let condensed = CsvFormat {
+        alignment: false,
+        ..Default::default()
+    };
+    let file = std::io::Cursor::new(TEST_CSV.as_bytes().to_vec());
+    match CsvAppender::new(file, condensed.clone()) {
+        Err(AppendCsvError::HeaderMismatch { expected, found }) => {
+            assert_eq!(expected, condensed.to_string());
+            assert_eq!(found, TEST_CSV.lines().next().unwrap());
+        }
+        Err(e) => panic!("unexpected error {:?}", e),
+        Ok(_) => panic!("header mismatch not detected"),
+    }
+
+    let content = "Time;StationNumber;t;EOR\r\n202501101350;44527;4.3;EOR";
+    let format = CsvFormat::from_str("Time;StationNumber;t;EOR").unwrap();
+    let mut file = std::io::Cursor::new(content.as_bytes().to_vec());
+    let mut appender = CsvAppender::new(&mut file, format.clone()).unwrap();
+    let record = RawRecord::from_csv("202501101400;44527;4.1;EOR", &format).unwrap();
+    assert!(appender.write(&record).unwrap());
+    appender.flush().unwrap();
+    drop(appender);
+    assert_eq!(
+        String::from_utf8(file.into_inner()).unwrap(),
+        format!("{}\r\n202501101400;44527;4.1;EOR\r\n", content),
+    );
+}
@@ -0,0 +1,248 @@
+/*
+    Created by Zoltan Kovari, 2025.
+
+    Licensed under the Apache License, Version 2.0
+    http://www.apache.org/licenses/LICENSE-2.0
+    (see LICENSE.txt)
+*/
+
+//! Long-running mode, polling the latest 10-minute data on the publication schedule of ODP.
+
+use crate::csv::convert_csv_into;
+use crate::download::{Client, Transport, UreqTransport};
+use crate::error::*;
+use crate::record::RecordFilter;
+use crate::sink::RecordSink;
+use crate::zip::unzip_named;
+
+use std::io::Cursor;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[cfg(test)]
+mod test;
+
+/// Source of the current time, and a way to wait, so that the schedule can be tested.
+pub trait Clock {
+    fn now(&self) -> SystemTime;
+
+    fn sleep(&self, duration: Duration);
+}
+
+/// The `Clock` of the system.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        std::thread::sleep(duration)
+    }
+}
+
+/// Polling times aligned to the 10-minute periods of the data.
+///
+/// The polls are at `offset` after every multiple of `interval` (counted from the Unix epoch,
+/// i.e. in UTC), as the file of a period is published a few minutes after its end.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Schedule {
+    pub interval: Duration,
+    pub offset: Duration,
+}
+impl Default for Schedule {
+    /// Every 10 minutes, 9 minutes after the period (e.g. 14:09, 14:19, ...).
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(10 * 60),
+            offset: Duration::from_secs(9 * 60),
+        }
+    }
+}
+impl Schedule {
+    /// Returns the first polling time strictly after `time`.
+    pub fn next_after(&self, time: SystemTime) -> SystemTime {
+        let interval = self.interval.as_secs().max(1);
+        let offset = self.offset.as_secs() % interval;
+        let secs = time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let next = (secs + interval - offset) / interval * interval + offset;
+        UNIX_EPOCH + Duration::from_secs(next)
+    }
+}
+
+/// Delays of the retries after a failed (or unchanged) poll, doubled after each one.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Backoff {
+    pub initial: Duration,
+    pub max: Duration,
+}
+impl Default for Backoff {
+    /// From 30 seconds up to 5 minutes.
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_secs(30),
+            max: Duration::from_secs(5 * 60),
+        }
+    }
+}
+impl Backoff {
+    /// Returns the delay of the retry after `failures` consecutive failures (at least 1).
+    pub fn delay(&self, failures: u32) -> Duration {
+        let factor = 2u32.saturating_pow(failures.saturating_sub(1));
+        self.initial.saturating_mul(factor).min(self.max)
+    }
+}
+
+/// Result of a successful poll.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PollOutcome {
+    /// A new file was downloaded, and its records written to the sinks.
+    Processed { file: String, records: usize },
+    /// The file was already processed, nothing was written.
+    Unchanged { file: String },
+}
+
+/// Polls the latest data and writes the records of every new file to the sinks.
+///
+/// Files are told apart by the timestamp in their name (see `zip::unzip_named`), the ones with
+/// a timestamp not later than the last processed one are skipped. The last timestamp is only
+/// kept in memory, so after a restart the current file is processed again (the sinks should
+/// skip duplicates, e.g. `CsvAppender` or `Database`). With a cache on the client (see
+/// `Client::with_cache`), an unchanged file is not downloaded again.
+pub struct Daemon<C: Clock = SystemClock, T: Transport = UreqTransport> {
+    client: Client<T>,
+    clock: C,
+    schedule: Schedule,
+    backoff: Backoff,
+    stop: Arc<AtomicBool>,
+    last: Option<String>,
+}
+impl<T: Transport> Daemon<SystemClock, T> {
+    /// Constructs a daemon with the system clock, and the default schedule and backoff.
+    pub fn new(client: Client<T>) -> Self {
+        Self::with_clock(client, SystemClock)
+    }
+}
+impl<C: Clock, T: Transport> Daemon<C, T> {
+    /// Constructs a daemon with any clock, and the default schedule and backoff.
+    pub fn with_clock(client: Client<T>, clock: C) -> Self {
+        Self {
+            client,
+            clock,
+            schedule: Schedule::default(),
+            backoff: Backoff::default(),
+            stop: Arc::new(AtomicBool::new(false)),
+            last: None,
+        }
+    }
+
+    pub fn schedule(mut self, schedule: Schedule) -> Self {
+        self.schedule = schedule;
+        self
+    }
+
+    pub fn backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Returns the flag that stops `run` when set, checked at least every second while waiting.
+    pub fn stop_flag(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.stop)
+    }
+
+    /// Sets the stop flag on SIGTERM and SIGINT, so that `run` returns after the current poll.
+    ///
+    /// Requires the `signals` feature.
+    #[cfg(feature = "signals")]
+    pub fn stop_on_signals(&self) -> std::io::Result<()> {
+        for signal in [signal_hook::consts::SIGTERM, signal_hook::consts::SIGINT] {
+            signal_hook::flag::register(signal, self.stop_flag())?;
+        }
+        Ok(())
+    }
+
+    /// Returns the timestamp (or name) of the last processed file.
+    pub fn last_processed(&self) -> Option<&str> {
+        self.last.as_deref()
+    }
+
+    /// Polls immediately, then according to the schedule, until the stop flag is set.
+    ///
+    /// A new sink is opened with `open_sink` for every new file, and finished after its records.
+    /// Failed polls (including timeouts, see `UreqTransport::with_timeouts`) are retried with the
+    /// backoff, and so are unchanged files, as the new one is expected shortly. Every result is
+    /// passed to `report`, errors do not stop the loop.
+    pub fn run<'a>(
+        &mut self,
+        mut open_sink: impl FnMut() -> Result<Box<dyn RecordSink + 'a>, SinkError>,
+        record_filter: Option<&RecordFilter>,
+        mut report: impl FnMut(&Result<PollOutcome, DaemonError>),
+    ) {
+        let mut failures = 0;
+        while !self.stopped() {
+            let result = self.poll(&mut open_sink, record_filter);
+            report(&result);
+
+            let now = self.clock.now();
+            let next = self.schedule.next_after(now);
+            let next = match result {
+                Ok(PollOutcome::Processed { .. }) => {
+                    failures = 0;
+                    next
+                }
+                _ => {
+                    failures += 1;
+                    next.min(now + self.backoff.delay(failures))
+                }
+            };
+            self.wait_until(next);
+        }
+    }
+
+    /// Downloads the latest file, and writes its records to a new sink if it was not processed
+    /// yet.
+    pub fn poll<'a>(
+        &mut self,
+        open_sink: impl FnOnce() -> Result<Box<dyn RecordSink + 'a>, SinkError>,
+        record_filter: Option<&RecordFilter>,
+    ) -> Result<PollOutcome, DaemonError> {
+        let body = self.client.download_latest()?;
+        let (file, reader) = unzip_named(Cursor::new(body))?;
+        let key = file_timestamp(&file).unwrap_or(&file).to_string();
+        if self.last.as_ref().is_some_and(|last| key <= *last) {
+            return Ok(PollOutcome::Unchanged { file });
+        }
+
+        let mut sink = open_sink()?;
+        let records = convert_csv_into(reader, record_filter, &mut sink)?;
+        self.last = Some(key);
+        Ok(PollOutcome::Processed { file, records })
+    }
+
+    fn stopped(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
+
+    /// Sleeps in steps of at most a second, so that the stop flag is noticed.
+    fn wait_until(&self, time: SystemTime) {
+        while !self.stopped() {
+            match time.duration_since(self.clock.now()) {
+                Ok(left) if !left.is_zero() => self.clock.sleep(left.min(Duration::from_secs(1))),
+                _ => break,
+            }
+        }
+    }
+}
+
+/// Returns the timestamp in a file name like `HABP_10M_SYNOP_20250110140805.csv`.
+fn file_timestamp(name: &str) -> Option<&str> {
+    let stem = name.rsplit_once('.').map_or(name, |(stem, _)| stem);
+    let timestamp = stem.rsplit('_').next()?;
+    match timestamp.len() == 14 && timestamp.bytes().all(|b| b.is_ascii_digit()) {
+        true => Some(timestamp),
+        false => None,
+    }
+}
@@ -0,0 +1,150 @@
+/*
+    Created by Zoltan Kovari, 2025.
+
+    Licensed under the Apache License, Version 2.0
+    http://www.apache.org/licenses/LICENSE-2.0
+    (see LICENSE.txt)
+*/
+
+use super::*;
+
+use crate::download::test::{response, serve};
+use crate::record::test::TEST_CSV;
+use crate::sink::SinkRecord;
+use crate::zip::{test::TEST_ZIP, zip};
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+/// 2025-01-10 14:03:20 UTC.
+const START: u64 = 1_736_517_800;
+
+fn time(secs_after_start: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(START + secs_after_start)
+}
+
+/// Clock that only advances when sleeping.
+#[derive(Clone)]
+struct FakeClock(Rc<Cell<SystemTime>>);
+impl Clock for FakeClock {
+    fn now(&self) -> SystemTime {
+        self.0.get()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.0.set(self.0.get() + duration);
+    }
+}
+
+/// Sink that only counts the records.
+struct Counter(Rc<Cell<usize>>);
+impl RecordSink for Counter {
+    fn write(&mut self, _record: &SinkRecord) -> Result<(), SinkError> {
+        self.0.set(self.0.get() + 1);
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), SinkError> {
+        Ok(())
+    }
+}
+
+// Checking the polling times.
+#[test]
+fn test_schedule() {
+    let schedule = Schedule::default();
+    assert_eq!(schedule.next_after(time(0)), time(340));
+    assert_eq!(schedule.next_after(time(340)), time(940));
+    assert_eq!(schedule.next_after(time(939)), time(940));
+
+    let backoff = Backoff::default();
+    let delays: Vec<u64> = (1..=6).map(|n| backoff.delay(n).as_secs()).collect();
+    assert_eq!(delays, [30, 60, 120, 240, 300, 300]);
+    assert_eq!(backoff.delay(100), backoff.max);
+
+    assert_eq!(file_timestamp("HABP_10M_SYNOP_20250110140805.csv"), Some("20250110140805"));
+    assert_eq!(file_timestamp("HABP_10M_SYNOP_LATEST.csv"), None);
+}
+
+// Checking a run against the stand-in server: a new file, the same file again, a failure, then
+// a newer file, with the retries and the stop flag.
+#[test]
+fn test_daemon_run() {
+    let mut newer = Cursor::new(Vec::new());
+    let lines: Vec<&str> = TEST_CSV.split_inclusive('\n').take(3).collect();
+    zip(lines.concat().as_bytes(), &mut newer, "HABP_10M_SYNOP_20250110141805.csv").unwrap();
+
+    let (base_url, handle) = serve(vec![
+        response(200, &[], TEST_ZIP),
+        response(200, &[], TEST_ZIP),
+        response(500, &[], b"Internal Server Error"),
+        response(200, &[], newer.get_ref()),
+    ]);
+    let clock = FakeClock(Rc::new(Cell::new(time(0))));
+    let client = Client::with_transport(&base_url, UreqTransport::new());
+    let mut daemon = Daemon::with_clock(client, clock.clone());
+    let stop = daemon.stop_flag();
+
+    let count = Rc::new(Cell::new(0));
+    let results = RefCell::new(Vec::new());
+    daemon.run(
+        || Ok(Box::new(Counter(Rc::clone(&count)))),
+        None,
+        |result| {
+            let mut results = results.borrow_mut();
+            results.push((clock.now(), result.as_ref().ok().cloned()));
+            if results.len() == 4 {
+                stop.store(true, Ordering::Relaxed);
+            }
+        },
+    );
+    handle.join().unwrap();
+
+    let processed = |file: &str, records| PollOutcome::Processed {
+        file: file.to_string(),
+        records,
+    };
+    let unchanged = PollOutcome::Unchanged {
+        file: "HABP_10M_SYNOP_20250110140805.csv".to_string(),
+    };
+    assert_eq!(results.into_inner(), vec![
+        (time(0), Some(processed("HABP_10M_SYNOP_20250110140805.csv", 286))),
+        (time(340), Some(unchanged)),
+        (time(370), None),
+        (time(430), Some(processed("HABP_10M_SYNOP_20250110141805.csv", 2))),
+    ]);
+    assert_eq!(count.get(), 288);
+    assert_eq!(daemon.last_processed(), Some("20250110141805"));
+    // The stop flag is noticed without waiting for the next poll
+    assert_eq!(clock.now(), time(430));
+}
+
+// Checking that a stalled server times out, and is retried with the backoff.
+#[test]
+fn test_daemon_timeout() {
+    let (base_url, handle) = serve(vec![Vec::new(), response(200, &[], TEST_ZIP)]);
+    let clock = FakeClock(Rc::new(Cell::new(time(0))));
+    let timeout = Duration::from_millis(200);
+    let client = Client::with_transport(&base_url, UreqTransport::with_timeouts(timeout, timeout));
+    let mut daemon = Daemon::with_clock(client, clock.clone());
+    let stop = daemon.stop_flag();
+
+    let count = Rc::new(Cell::new(0));
+    let results = RefCell::new(Vec::new());
+    daemon.run(
+        || Ok(Box::new(Counter(Rc::clone(&count)))),
+        None,
+        |result| {
+            let timed_out = matches!(result, Err(DaemonError::Download(DownloadError::Timeout(_))));
+            let mut results = results.borrow_mut();
+            results.push((clock.now(), timed_out, result.is_ok()));
+            if results.len() == 2 {
+                stop.store(true, Ordering::Relaxed);
+            }
+        },
+    );
+    handle.join().unwrap();
+
+    assert_eq!(results.into_inner(), [(time(0), true, false), (time(30), false, true)]);
+    assert_eq!(count.get(), 286);
+}
@@ -0,0 +1,168 @@
+/*
+    Created by Zoltan Kovari, 2025.
+
+    Licensed under the Apache License, Version 2.0
+    http://www.apache.org/licenses/LICENSE-2.0
+    (see LICENSE.txt)
+*/
+
+//! SQLite database output, available with the `sqlite` feature.
+
+use crate::error::*;
+use crate::field::{Field, FieldConfig};
+use crate::record::{MetRecord, MetValue};
+use crate::sql::{column_type, create_table_sql, Dialect, TABLE_NAME};
+
+use std::borrow::Borrow;
+use std::path::Path;
+
+use rusqlite::types::Value;
+use rusqlite::{Connection, OptionalExtension};
+
+#[cfg(test)]
+mod test;
+
+/// Number of records inserted in a single transaction.
+pub(crate) const BATCH_SIZE: usize = 1000;
+
+/// A database with a `data` table compatible with a `FieldConfig`.
+///
+/// The table is the same as in the SQL scripts (see `create_table_sql`), `Time` is stored as text
+/// in the format of `Timestamp::to_iso_string`.
+pub struct Database {
+    conn: Connection,
+    fields: Vec<Field>,
+}
+impl Database {
+    /// Opens or creates a database file, see `from_connection`.
+    pub fn open(path: impl AsRef<Path>, field_config: &FieldConfig) -> Result<Self, DbError> {
+        Self::from_connection(Connection::open(path)?, field_config)
+    }
+
+    /// Creates a temporary database in memory, mainly for testing.
+    pub fn open_in_memory(field_config: &FieldConfig) -> Result<Self, DbError> {
+        Self::from_connection(Connection::open_in_memory()?, field_config)
+    }
+
+    /// Creates the `data` table if it does not exist yet, otherwise checks that it is compatible
+    /// with the selected fields.
+    ///
+    /// An existing table is compatible if it has a column of the right type for each field, and
+    /// the same primary key. It may have other columns too, these are left empty on insert.
+    pub fn from_connection(conn: Connection, field_config: &FieldConfig) -> Result<Self, DbError> {
+        let fields: Vec<Field> = field_config
+            .fields()
+            .filter(|f| column_type(**f, Dialect::Sqlite).is_some())
+            .copied()
+            .collect();
+
+        let exists = conn
+            .query_row(
+                "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1",
+                [TABLE_NAME],
+                |_| Ok(()),
+            )
+            .optional()?
+            .is_some();
+        match exists {
+            true => check_table(&conn, &fields)?,
+            false => conn.execute_batch(&create_table_sql(field_config, Dialect::Sqlite))?,
+        }
+
+        Ok(Self { conn, fields })
+    }
+
+    /// Returns the underlying connection, e.g. for queries.
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// Inserts the records, skipping those that are already present (by primary key), so that
+    /// re-runs are idempotent.
+    ///
+    /// Records are inserted in transactions of `BATCH_SIZE`. Returns the number of records that
+    /// were actually inserted.
+    pub fn insert<R: Borrow<MetRecord>>(
+        &mut self,
+        records: impl IntoIterator<Item = R>,
+    ) -> Result<usize, DbError> {
+        let sql = format!(
+            "INSERT OR IGNORE INTO \"{}\" ({}) VALUES ({})",
+            TABLE_NAME,
+            self.fields
+                .iter()
+                .map(|f| format!("\"{}\"", f.title()))
+                .collect::<Vec<String>>()
+                .join(", "),
+            (1..=self.fields.len())
+                .map(|i| format!("?{}", i))
+                .collect::<Vec<String>>()
+                .join(", "),
+        );
+
+        let mut inserted = 0;
+        let mut records = records.into_iter().peekable();
+        while records.peek().is_some() {
+            let tx = self.conn.transaction()?;
+            {
+                let mut stmt = tx.prepare_cached(&sql)?;
+                for record in records.by_ref().take(BATCH_SIZE) {
+                    let record = record.borrow();
+                    let values = self.fields.iter().map(|f| sql_value(record.get(*f)));
+                    inserted += stmt.execute(rusqlite::params_from_iter(values))?;
+                }
+            }
+            tx.commit()?;
+        }
+
+        Ok(inserted)
+    }
+}
+
+/// Checks the columns and the primary key of the existing `data` table.
+fn check_table(conn: &Connection, fields: &[Field]) -> Result<(), DbError> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info(\"{}\")", TABLE_NAME))?;
+    // Name, type, and position in the primary key (0 if not part of it)
+    let columns = stmt
+        .query_map([], |row| Ok((row.get(1)?, row.get(2)?, row.get(5)?)))?
+        .collect::<Result<Vec<(String, String, i64)>, rusqlite::Error>>()?;
+
+    for field in fields {
+        let expected = column_type(*field, Dialect::Sqlite).unwrap_or_default();
+        match columns.iter().find(|(name, _, _)| name == field.title()) {
+            None => return Err(DbError::MissingColumn(*field)),
+            Some((_, ty, _)) if !ty.eq_ignore_ascii_case(expected) => {
+                return Err(DbError::ColumnType {
+                    field: *field,
+                    expected,
+                    found: ty.clone(),
+                });
+            }
+            Some(_) => (),
+        }
+    }
+
+    let mut key: Vec<(i64, String)> = columns
+        .into_iter()
+        .filter(|(_, _, pk)| *pk > 0)
+        .map(|(name, _, pk)| (pk, name))
+        .collect();
+    key.sort();
+    let key: Vec<String> = key.into_iter().map(|(_, name)| name).collect();
+    if key != [Field::Time.title(), Field::StationNumber.title()] {
+        return Err(DbError::PrimaryKey(key));
+    }
+
+    Ok(())
+}
+
+/// Converts a value for binding to a statement.
+fn sql_value(value: Option<MetValue>) -> Value {
+    match value {
+        None => Value::Null,
+        Some(MetValue::Timestamp(ts)) => Value::Text(ts.to_iso_string()),
+        Some(MetValue::Integer(n)) => Value::Integer(n as i64),
+        Some(MetValue::Float(x)) => Value::Real(x),
+        Some(MetValue::Text(s)) => Value::Text(s.to_string()),
+    }
+}
@@ -0,0 +1,90 @@
+/*
+    Created by Zoltan Kovari, 2025.
+
+    Licensed under the Apache License, Version 2.0
+    http://www.apache.org/licenses/LICENSE-2.0
+    (see LICENSE.txt)
+*/
+
+use super::*;
+
+use crate::csv::read_csv;
+use crate::format::Delimiter;
+use crate::record::test::TEST_CSV;
+
+fn test_records() -> Vec<MetRecord> {
+    let (records, _) = read_csv(TEST_CSV.as_bytes(), None).unwrap();
+    records
+        .map(|r| MetRecord::try_from(r.unwrap()).unwrap())
+        .collect()
+}
+
+// Checking that the table is created, and re-inserting the same records changes nothing.
+#[test]
+fn test_insert() {
+    let records = test_records();
+    let mut db = Database::open_in_memory(&FieldConfig::default()).unwrap();
+    assert_eq!(db.insert(&records).unwrap(), records.len());
+    assert_eq!(db.insert(records.iter().take(10)).unwrap(), 0);
+
+    let count: i64 = db
+        .connection()
+        .query_row("SELECT COUNT(*) FROM data", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(count as usize, records.len());
+
+    let (temp, q_temp, name): (Option<f64>, Option<String>, String) = db
+        .connection()
+        .query_row(
+            "SELECT t, Q_t, StationName FROM data WHERE Time = ?1 AND StationNumber = ?2",
+            ("2025-01-10 13:50:00", 44527),
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .unwrap();
+    assert_eq!(temp, Some(4.3));
+    assert_eq!(q_temp, None);
+    assert_eq!(name, "Budapest Pestszentlőrinc");
+}
+
+// Checking the compatibility check of an existing table.
+#[test]
+fn test_existing_table() {
+    let config = |header| FieldConfig::from_header(header, Delimiter::default()).unwrap();
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(&create_table_sql(&config("Time;StationNumber;t;fx;EOR"), Dialect::Sqlite))
+        .unwrap();
+
+    // A subset of the columns is compatible
+    let mut db = Database::from_connection(conn, &config("Time;StationNumber;t")).unwrap();
+    assert_eq!(db.insert(test_records()).unwrap(), TEST_CSV.lines().count() - 1);
+    let conn = db.conn;
+
+    assert!(matches!(
+        Database::from_connection(conn, &config("Time;StationNumber;ta")),
+        Err(DbError::MissingColumn(Field::TempAvg))
+    ));
+
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch("CREATE TABLE data (Time TEXT, StationNumber TEXT, t REAL)")
+        .unwrap();
+    match Database::from_connection(conn, &config("Time;StationNumber;t")) {
+        Err(DbError::ColumnType {
+            field,
+            expected,
+            found,
+        }) => {
+            assert_eq!(field, Field::StationNumber);
+            assert_eq!(expected, "INTEGER");
+            assert_eq!(found, "TEXT");
+        }
+        _ => panic!("expected a column type error"),
+    }
+
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch("CREATE TABLE data (Time TEXT, StationNumber INTEGER PRIMARY KEY, t REAL)")
+        .unwrap();
+    match Database::from_connection(conn, &config("Time;StationNumber;t")) {
+        Err(DbError::PrimaryKey(key)) => assert_eq!(key, ["StationNumber"]),
+        _ => panic!("expected a primary key error"),
+    }
+}
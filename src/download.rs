@@ -6,16 +6,667 @@
     (see LICENSE.txt)
 */
 
-use std::io::Read;
+//! Functions for downloading files from ODP.
 
-fn download_latest() {
-    todo!();
+use crate::error::*;
+use crate::field::glob;
+use crate::timestamp::Timestamp;
+
+use std::error::Error;
+use std::fs::File;
+use std::io::{Cursor, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[cfg(test)]
+pub(crate) mod test;
+
+/// Root URL of the observation datasets on ODP.
+pub const ODP_ROOT: &str = "https://odp.met.hu/climate/observations_hungary/";
+
+/// Path of the latest 10-minute synoptic data, relative to `ODP_ROOT`.
+pub const LATEST_PATH: &str = "10_minutes/now/HABP_10M_SYNOP_LATEST.csv.zip";
+
+/// Downloads the latest 10-minute synoptic data with a default `Client`.
+pub fn download_latest() -> Result<Vec<u8>, DownloadError> {
+    Client::new().download_latest()
+}
+
+/// Downloads a file under `ODP_ROOT` with a default `Client`.
+pub fn download_with_path(path: &str) -> Result<Vec<u8>, DownloadError> {
+    Client::new().download_with_path(path)
+}
+
+/// A response as returned by a `Transport`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+impl Response {
+    /// Returns the value of the first header with the given name (case-insensitively).
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
 }
 
-fn download_with_path<T>(path: &str) -> Result<Box<T>, DownloadError>
-where T: Read
-{
-    todo!()
+/// A response with a body that is read as it arrives, see `Transport::get_reader`.
+pub struct ResponseReader {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Box<dyn Read + Send>,
+}
+impl ResponseReader {
+    /// Returns the value of the first header with the given name (case-insensitively).
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+}
+
+fn find_header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|(_, v)| v.as_str())
+}
+
+/// Abstraction of the HTTP layer used by `Client`.
+///
+/// Implementations should return every response as it is, regardless of the status code. Errors
+/// are meant for the cases when there is no response at all (or when it cannot be read).
+pub trait Transport {
+    /// Performs a GET request with the given extra headers.
+    fn get(&self, url: &str, headers: &[(&str, &str)]) -> Result<Response, DownloadError>;
+
+    /// Same as `get`, but the body is returned as a reader, so that it can be processed (e.g.
+    /// written to a file) as it arrives.
+    ///
+    /// The default implementation reads the whole body with `get` first.
+    fn get_reader(
+        &self,
+        url: &str,
+        headers: &[(&str, &str)],
+    ) -> Result<ResponseReader, DownloadError> {
+        let response = self.get(url, headers)?;
+        Ok(ResponseReader {
+            status: response.status,
+            headers: response.headers,
+            body: Box::new(Cursor::new(response.body)),
+        })
+    }
+}
+
+/// Default time limit of connecting to the server, see `UreqTransport::with_timeouts`.
+pub const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default time limit of a single read from the server, see `UreqTransport::with_timeouts`.
+pub const READ_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The default `Transport`, based on `ureq`.
+#[derive(Clone, Debug)]
+pub struct UreqTransport {
+    agent: ureq::Agent,
+}
+impl UreqTransport {
+    /// Constructs an instance with a default agent, and the default timeouts.
+    pub fn new() -> Self {
+        Self::with_timeouts(CONNECT_TIMEOUT, READ_TIMEOUT)
+    }
+
+    /// Constructs an instance with a default agent, giving up on connecting after `connect`, and
+    /// on a stalled response when no data arrives for `read`.
+    ///
+    /// Both fail with `DownloadError::Timeout`, so that a stalled server does not block the
+    /// caller (e.g. the `Daemon`) indefinitely.
+    pub fn with_timeouts(connect: Duration, read: Duration) -> Self {
+        Self {
+            agent: ureq::AgentBuilder::new()
+                .user_agent(concat!("hungaromet-odp-client/", env!("CARGO_PKG_VERSION")))
+                .timeout_connect(connect)
+                .timeout_read(read)
+                .build(),
+        }
+    }
+}
+impl Default for UreqTransport {
+    /// Calls `new`.
+    fn default() -> Self {
+        Self::new()
+    }
 }
+impl Transport for UreqTransport {
+    fn get(&self, url: &str, headers: &[(&str, &str)]) -> Result<Response, DownloadError> {
+        let mut response = self.get_reader(url, headers)?;
+
+        // A prematurely closed connection is detected by `Client` from the length, if known.
+        // Otherwise (e.g. a chunked body without the last chunk) it is an error here.
+        let has_length = response.header("Content-Length").is_some();
+        let mut body = Vec::new();
+        match response.body.read_to_end(&mut body) {
+            Ok(_) => (),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof && has_length => (),
+            Err(e) => return Err(read_error(url, e)),
+        }
+
+        Ok(Response {
+            status: response.status,
+            headers: response.headers,
+            body,
+        })
+    }
+
+    fn get_reader(
+        &self,
+        url: &str,
+        headers: &[(&str, &str)],
+    ) -> Result<ResponseReader, DownloadError> {
+        let mut request = self.agent.get(url);
+        for (name, value) in headers {
+            request = request.set(name, value);
+        }
+
+        let response = match request.call() {
+            Ok(r) | Err(ureq::Error::Status(_, r)) => r,
+            Err(ureq::Error::Transport(t)) => {
+                let timed_out = t
+                    .source()
+                    .and_then(|e| e.downcast_ref::<std::io::Error>())
+                    .is_some_and(is_timeout);
+                return Err(match t.kind() {
+                    _ if timed_out => DownloadError::Timeout(url.to_string()),
+                    ureq::ErrorKind::InvalidUrl | ureq::ErrorKind::UnknownScheme => {
+                        DownloadError::InvalidUrl(url.to_string())
+                    }
+                    _ => DownloadError::Connect {
+                        url: url.to_string(),
+                        source: Box::new(t),
+                    },
+                });
+            }
+        };
+
+        let status = response.status();
+        let headers: Vec<(String, String)> = response
+            .headers_names()
+            .into_iter()
+            .filter_map(|n| response.header(&n).map(|v| (n.clone(), v.to_string())))
+            .collect();
+
+        Ok(ResponseReader {
+            status,
+            headers,
+            body: Box::new(response.into_reader()),
+        })
+    }
+}
+
+/// Converts an error of reading the body, telling apart the timeouts.
+pub(crate) fn read_error(url: &str, e: std::io::Error) -> DownloadError {
+    match is_timeout(&e) {
+        true => DownloadError::Timeout(url.to_string()),
+        false => DownloadError::Io {
+            url: url.to_string(),
+            source: e,
+        },
+    }
+}
+
+/// Whether an I/O error is caused by a timeout of the socket (reported as `WouldBlock` on some
+/// platforms).
+fn is_timeout(e: &std::io::Error) -> bool {
+    matches!(e.kind(), ErrorKind::TimedOut | ErrorKind::WouldBlock)
+}
+
+/// Result of a conditional download, see `Client::download_if_modified`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Conditional {
+    /// The file was downloaded.
+    Modified(Vec<u8>),
+    /// The server answered that the file did not change, the body is the cached one.
+    NotModified(Vec<u8>),
+}
+impl Conditional {
+    /// Returns the body, whether downloaded or cached.
+    pub fn into_body(self) -> Vec<u8> {
+        match self {
+            Self::Modified(body) | Self::NotModified(body) => body,
+        }
+    }
+}
+
+/// Local cache of downloaded files, with their `ETag` and `Last-Modified` headers.
+///
+/// Each URL is stored in two files named after its 64-bit FNV-1a hash: `<hash>.body` with the
+/// content, and `<hash>.meta` with the URL and the headers (one `name: value` line each). Only
+/// responses with at least one of the headers are stored, as the others cannot be validated.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DownloadCache {
+    dir: PathBuf,
+}
+impl DownloadCache {
+    /// Constructs a cache in a directory, which is created when the first file is stored.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Returns the directory of the cache.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the name of the files for the URL, without extension.
+    pub fn key(url: &str) -> String {
+        let hash = url.bytes().fold(0xcbf29ce484222325_u64, |hash, b| {
+            (hash ^ u64::from(b)).wrapping_mul(0x100000001b3)
+        });
+        format!("{:016x}", hash)
+    }
+
+    /// Returns the cached body of the URL, if any.
+    pub fn body(&self, url: &str) -> Result<Option<Vec<u8>>, DownloadError> {
+        match self.validators(url)? {
+            Some(_) => self.read(url, "body"),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the cached `ETag` and `Last-Modified` headers of the URL, `None` if the URL is
+    /// not cached.
+    fn validators(&self, url: &str) -> Result<Option<Vec<(String, String)>>, DownloadError> {
+        let Some(meta) = self.read(url, "meta")? else {
+            return Ok(None);
+        };
+        let meta = String::from_utf8_lossy(&meta);
+        let mut lines = meta.lines().filter_map(|l| l.split_once(": "));
+        // The URL is checked in case of a hash collision
+        match lines.next() {
+            Some(("url", cached)) if cached == url => (),
+            _ => return Ok(None),
+        }
+        Ok(Some(lines.map(|(n, v)| (n.to_string(), v.to_string())).collect()))
+    }
+
+    /// Stores the response of the URL, if it has any of the headers.
+    fn store(&self, url: &str, response: &Response) -> Result<(), DownloadError> {
+        let validators: Vec<String> = ["ETag", "Last-Modified"]
+            .into_iter()
+            .filter_map(|name| response.header(name).map(|v| format!("{}: {}\n", name, v)))
+            .collect();
+        if validators.is_empty() {
+            return Ok(());
+        }
+        let meta = format!("url: {}\n{}", url, validators.concat());
+
+        let error = |path: &Path| {
+            let path = path.display().to_string();
+            move |source| DownloadError::Cache { path, source }
+        };
+        std::fs::create_dir_all(&self.dir).map_err(error(&self.dir))?;
+        // The metadata is written last, so that it never refers to an older body
+        let files = [("body", response.body.as_slice()), ("meta", meta.as_bytes())];
+        for (extension, content) in files {
+            let path = self.path(url, extension);
+            let temp = self.path(url, &format!("{}.tmp", extension));
+            File::create(&temp)
+                .and_then(|mut f| f.write_all(content))
+                .and_then(|_| std::fs::rename(&temp, &path))
+                .map_err(error(&path))?;
+        }
+        Ok(())
+    }
 
-struct DownloadError;
+    fn path(&self, url: &str, extension: &str) -> PathBuf {
+        self.dir.join(format!("{}.{}", Self::key(url), extension))
+    }
+
+    fn read(&self, url: &str, extension: &str) -> Result<Option<Vec<u8>>, DownloadError> {
+        let path = self.path(url, extension);
+        match std::fs::read(&path) {
+            Ok(content) => Ok(Some(content)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(DownloadError::Cache {
+                path: path.display().to_string(),
+                source: e,
+            }),
+        }
+    }
+}
+
+/// Client for downloading files from ODP, or a similar server.
+#[derive(Clone, Debug)]
+pub struct Client<T = UreqTransport> {
+    base_url: String,
+    transport: T,
+    cache: Option<DownloadCache>,
+}
+impl Client {
+    /// Constructs a client for `ODP_ROOT` with the default transport.
+    pub fn new() -> Self {
+        Self::with_transport(ODP_ROOT, UreqTransport::new())
+    }
+}
+impl Default for Client {
+    /// Calls `new`.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<T: Transport> Client<T> {
+    /// Constructs a client for any base URL with a custom transport.
+    pub fn with_transport(base_url: &str, transport: T) -> Self {
+        let mut base_url = base_url.to_string();
+        if !base_url.ends_with('/') {
+            base_url.push('/');
+        }
+        Self {
+            base_url,
+            transport,
+            cache: None,
+        }
+    }
+
+    /// Sets a cache, so that files are only downloaded again if they changed, see
+    /// `download_if_modified`.
+    pub fn with_cache(mut self, cache: DownloadCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Returns the cache, if any.
+    pub fn cache(&self) -> Option<&DownloadCache> {
+        self.cache.as_ref()
+    }
+
+    /// Returns the base URL, always ending with a slash.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Returns the full URL for a path relative to the base URL.
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path.trim_start_matches('/'))
+    }
+
+    /// Downloads the latest 10-minute synoptic data (see `LATEST_PATH`).
+    pub fn download_latest(&self) -> Result<Vec<u8>, DownloadError> {
+        self.download_with_path(LATEST_PATH)
+    }
+
+    /// Downloads a file by its path relative to the base URL.
+    pub fn download_with_path(&self, path: &str) -> Result<Vec<u8>, DownloadError> {
+        self.download_url(&self.url(path))
+    }
+
+    /// Downloads a file by its absolute URL, which does not have to be under the base URL.
+    ///
+    /// With a cache, the cached body is returned if the file did not change.
+    pub fn download_url(&self, url: &str) -> Result<Vec<u8>, DownloadError> {
+        Ok(self.download_if_modified(url)?.into_body())
+    }
+
+    /// Downloads the latest 10-minute synoptic data, if it changed since the cached one.
+    pub fn download_latest_if_modified(&self) -> Result<Conditional, DownloadError> {
+        self.download_if_modified(&self.url(LATEST_PATH))
+    }
+
+    /// Downloads a file by its absolute URL, if it changed since the cached one.
+    ///
+    /// If the URL is cached (with its body), its `ETag` and `Last-Modified` headers are sent back
+    /// in `If-None-Match` and `If-Modified-Since`, and the cached body is returned on a 304
+    /// answer.
+    /// Otherwise (or without a cache) the file is simply downloaded, and stored in the cache.
+    pub fn download_if_modified(&self, url: &str) -> Result<Conditional, DownloadError> {
+        let Some(cache) = &self.cache else {
+            let response = self.get(url, &[])?;
+            check_response(url, &response, &[200])?;
+            return Ok(Conditional::Modified(response.body));
+        };
+
+        // Without the cached body (e.g. deleted), a 304 answer could not be used
+        let cached = cache.body(url)?;
+        let validators = match cached {
+            Some(_) => cache.validators(url)?.unwrap_or_default(),
+            None => Vec::new(),
+        };
+        let headers: Vec<(&str, &str)> = validators
+            .iter()
+            .filter_map(|(name, value)| match name.as_str() {
+                "ETag" => Some(("If-None-Match", value.as_str())),
+                "Last-Modified" => Some(("If-Modified-Since", value.as_str())),
+                _ => None,
+            })
+            .collect();
+        let response = self.get(url, &headers)?;
+        if let (304, Some(body)) = (response.status, cached) {
+            if !headers.is_empty() {
+                return Ok(Conditional::NotModified(body));
+            }
+        }
+
+        check_response(url, &response, &[200])?;
+        cache.store(url, &response)?;
+        Ok(Conditional::Modified(response.body))
+    }
+
+    /// Downloads and parses a directory listing page by its path relative to the base URL, see
+    /// `parse_listing`.
+    pub fn list(&self, dir: &str) -> Result<Vec<ListingEntry>, DownloadError> {
+        let mut path = dir.to_string();
+        if !path.ends_with('/') {
+            path.push('/');
+        }
+        let page = self.download_with_path(&path)?;
+        Ok(parse_listing(&String::from_utf8_lossy(&page)))
+    }
+
+    /// Lists a directory and returns the newest file matching the pattern, see
+    /// `newest_matching`.
+    pub fn find_newest(
+        &self,
+        dir: &str,
+        pattern: &str,
+    ) -> Result<Option<ListingEntry>, DownloadError> {
+        Ok(newest_matching(&self.list(dir)?, pattern).cloned())
+    }
+
+    /// Performs a request with the transport, without checking the response.
+    pub(crate) fn get(
+        &self,
+        url: &str,
+        headers: &[(&str, &str)],
+    ) -> Result<Response, DownloadError> {
+        self.transport.get(url, headers)
+    }
+
+    /// Same as `get`, with the body read as it arrives.
+    pub(crate) fn get_reader(
+        &self,
+        url: &str,
+        headers: &[(&str, &str)],
+    ) -> Result<ResponseReader, DownloadError> {
+        self.transport.get_reader(url, headers)
+    }
+}
+
+/// Checks the status code against the accepted ones, and the body length against the
+/// `Content-Length` header (if present).
+pub(crate) fn check_response(
+    url: &str,
+    response: &Response,
+    accepted: &[u16],
+) -> Result<(), DownloadError> {
+    if !accepted.contains(&response.status) {
+        return Err(DownloadError::Status {
+            url: url.to_string(),
+            status: response.status,
+        });
+    }
+
+    check_length(url, response.header("Content-Length"), response.body.len() as u64)
+}
+
+/// Checks the number of received bytes against the `Content-Length` header (if present).
+pub(crate) fn check_length(
+    url: &str,
+    content_length: Option<&str>,
+    received: u64,
+) -> Result<(), DownloadError> {
+    let expected = content_length.and_then(|v| v.trim().parse::<u64>().ok());
+    match expected {
+        Some(expected) if expected != received => Err(DownloadError::Truncated {
+            url: url.to_string(),
+            expected,
+            received,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// An entry of a directory listing page.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ListingEntry {
+    pub name: String,
+    /// Size in bytes, only approximate if the page shows it rounded (e.g. `18M`). `None` if it
+    /// is not shown, as for directories.
+    pub size: Option<u64>,
+    /// Modification time as shown on the page (in the time zone of the server).
+    pub modified: Option<Timestamp>,
+    pub is_dir: bool,
+}
+
+/// Parses an HTML directory index, as generated by the web server of ODP.
+///
+/// Both the plain (`<pre>`) and the table layout are recognized, where each link is followed by
+/// the modification time and the size. Links to the parent directory and for sorting are
+/// skipped, and so is anything else that cannot be parsed, so a page that is not a listing gives
+/// no entries.
+pub fn parse_listing(html: &str) -> Vec<ListingEntry> {
+    let mut entries = Vec::new();
+    for link in html.split("<a ").skip(1) {
+        let Some((_, href)) = link.split_once("href=\"") else {
+            continue;
+        };
+        let href = href.split('"').next().unwrap_or_default().replace("&amp;", "&");
+        let (text, rest) = match link.split_once('>') {
+            Some((_, after)) => after.split_once("</a>").unwrap_or((after, "")),
+            None => continue,
+        };
+        if href.is_empty()
+            || href.starts_with(['?', '#'])
+            || href.contains("://")
+            || href == "../"
+            || text.trim() == "Parent Directory"
+        {
+            continue;
+        }
+
+        let is_dir = href.ends_with('/');
+        let last_segment = href.trim_end_matches('/').rsplit('/').next().unwrap_or_default();
+        let name = percent_decode(last_segment);
+        if name.is_empty() {
+            continue;
+        }
+        let columns = strip_tags(rest).replace("&nbsp;", " ");
+        let columns: Vec<&str> = columns.split_whitespace().collect();
+        let modified = match columns[..] {
+            [date, time, ..] => parse_listing_time(date, time),
+            _ => None,
+        };
+        let size = columns.get(2).and_then(|s| parse_listing_size(s));
+
+        entries.push(ListingEntry {
+            name,
+            size,
+            modified,
+            is_dir,
+        });
+    }
+    entries
+}
+
+/// Returns the newest file (not directory) with a name matching `pattern`, where `*` matches
+/// any number of characters (e.g. `HABP_10M_SYNOP_*.csv.zip`).
+///
+/// Files are compared by the modification time, then by the name.
+pub fn newest_matching<'a>(entries: &'a [ListingEntry], pattern: &str) -> Option<&'a ListingEntry> {
+    entries
+        .iter()
+        .filter(|e| !e.is_dir && glob(pattern, &e.name))
+        .max_by(|a, b| (a.modified, &a.name).cmp(&(b.modified, &b.name)))
+}
+
+/// Removes the HTML tags from a fragment.
+fn strip_tags(fragment: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for ch in fragment.chars() {
+        match ch {
+            '<' => in_tag = true,
+            '>' => {
+                in_tag = false;
+                text.push(' ');
+            }
+            _ if !in_tag => text.push(ch),
+            _ => (),
+        }
+    }
+    text
+}
+
+/// Decodes the `%XX` escapes of a link.
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|h| std::str::from_utf8(h).ok());
+        match (bytes[i], hex.and_then(|h| u8::from_str_radix(h, 16).ok())) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Parses a time like `10-Jan-2025 14:08` or `2025-01-10 14:08` (seconds are ignored).
+fn parse_listing_time(date: &str, time: &str) -> Option<Timestamp> {
+    const MONTHS: [&str; 12] =
+        ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+    let (year, month, day) = match date.split('-').collect::<Vec<&str>>()[..] {
+        [y, m, d] if y.len() == 4 => (y.parse().ok()?, m.parse().ok()?, d.parse().ok()?),
+        [d, m, y] => {
+            let month = MONTHS.iter().position(|name| name.eq_ignore_ascii_case(m))?;
+            (y.parse().ok()?, month as u8 + 1, d.parse().ok()?)
+        }
+        _ => return None,
+    };
+    let mut time = time.split(':');
+    let hour = time.next()?.parse().ok()?;
+    let minute = time.next()?.parse().ok()?;
+    Timestamp::new(year, month, day, hour, minute).ok()
+}
+
+/// Parses a size in bytes, or with a `K`, `M` or `G` suffix (powers of 1024).
+fn parse_listing_size(s: &str) -> Option<u64> {
+    let (number, exponent) = match s.char_indices().last()? {
+        (i, 'K' | 'k') => (&s[..i], 1),
+        (i, 'M') => (&s[..i], 2),
+        (i, 'G') => (&s[..i], 3),
+        _ => (s, 0),
+    };
+    match exponent {
+        0 => number.parse().ok(),
+        _ => {
+            let number: f64 = number.parse().ok()?;
+            Some((number * 1024f64.powi(exponent)).round() as u64)
+        }
+    }
+}
@@ -0,0 +1,277 @@
+/*
+    Created by Zoltan Kovari, 2025.
+
+    Licensed under the Apache License, Version 2.0
+    http://www.apache.org/licenses/LICENSE-2.0
+    (see LICENSE.txt)
+*/
+
+use super::*;
+
+use crate::record::test::TEST_CSV;
+use crate::zip::{test::TEST_ZIP, unzip};
+
+use std::io::{Cursor, Write};
+use std::net::TcpListener;
+use std::thread::JoinHandle;
+
+/// Minimal stand-in HTTP server, answering each connection with the next canned response, then
+/// closing it. An empty response stalls the connection, until the client gives up.
+///
+/// Returns the base URL and a handle that yields the received request heads when joined.
+pub(crate) fn serve(responses: Vec<Vec<u8>>) -> (String, JoinHandle<Vec<String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}/", listener.local_addr().unwrap());
+
+    let handle = std::thread::spawn(move || {
+        let mut requests = Vec::new();
+        for response in responses {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut head = Vec::new();
+            let mut buf = [0u8; 1];
+            while !head.ends_with(b"\r\n\r\n") && stream.read(&mut buf).unwrap() == 1 {
+                head.push(buf[0]);
+            }
+            requests.push(String::from_utf8(head).unwrap());
+            match response.is_empty() {
+                true => while stream.read(&mut buf).is_ok_and(|n| n > 0) {},
+                false => stream.write_all(&response).unwrap(),
+            }
+        }
+        requests
+    });
+
+    (base_url, handle)
+}
+
+/// Builds a raw HTTP response, with `Content-Length` set to the body length.
+pub(crate) fn response(status: u16, headers: &[(&str, &str)], body: &[u8]) -> Vec<u8> {
+    let mut raw = format!("HTTP/1.1 {} X\r\nConnection: close\r\n", status);
+    for (name, value) in headers {
+        raw.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    raw.push_str(&format!("Content-Length: {}\r\n\r\n", body.len()));
+
+    let mut raw = raw.into_bytes();
+    raw.extend_from_slice(body);
+    raw
+}
+
+// Checking a successful download from the stand-in server.
+#[test]
+fn test_download_latest() {
+    let (base_url, handle) = serve(vec![response(200, &[], TEST_ZIP)]);
+    let client = Client::with_transport(&base_url, UreqTransport::new());
+    let body = client.download_latest().unwrap();
+
+    let mut content = String::new();
+    unzip(Cursor::new(body))
+        .unwrap()
+        .read_to_string(&mut content)
+        .unwrap();
+    assert_eq!(content, TEST_CSV);
+
+    let requests = handle.join().unwrap();
+    assert!(requests[0].starts_with(&format!("GET /{} HTTP/1.1\r\n", LATEST_PATH)));
+}
+
+// Checking that the different failures are told apart.
+#[test]
+fn test_download_errors() {
+    let mut truncated = response(200, &[], TEST_ZIP);
+    truncated.truncate(truncated.len() - 100);
+    // The connection is closed before the last chunk, without a length to check
+    let chunked =
+        b"HTTP/1.1 200 X\r\nConnection: close\r\nTransfer-Encoding: chunked\r\n\r\n2\r\nPK\r\n";
+    let (base_url, handle) = serve(vec![
+        response(404, &[], b"Not Found"),
+        truncated,
+        chunked.to_vec(),
+        Vec::new(),
+    ]);
+    let client = Client::with_transport(&base_url, UreqTransport::new());
+
+    assert!(matches!(
+        client.download_with_path("missing.zip"),
+        Err(DownloadError::Status { status: 404, .. })
+    ));
+    match client.download_latest() {
+        Err(DownloadError::Truncated {
+            expected, received, ..
+        }) => {
+            assert_eq!(expected, TEST_ZIP.len() as u64);
+            assert_eq!(received, expected - 100);
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+    assert!(matches!(
+        client.download_latest(),
+        Err(DownloadError::Io { .. })
+    ));
+    // The server does not answer
+    let timeout = Duration::from_millis(200);
+    let client = Client::with_transport(&base_url, UreqTransport::with_timeouts(timeout, timeout));
+    assert!(matches!(
+        client.download_latest(),
+        Err(DownloadError::Timeout(_))
+    ));
+    handle.join().unwrap();
+
+    // Nothing is listening on the port any more
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}/", listener.local_addr().unwrap());
+    drop(listener);
+    let client = Client::with_transport(&base_url, UreqTransport::new());
+    assert!(matches!(
+        client.download_latest(),
+        Err(DownloadError::Connect { .. })
+    ));
+
+    let client = Client::with_transport("ftp://localhost/", UreqTransport::new());
+    assert!(matches!(
+        client.download_latest(),
+        Err(DownloadError::InvalidUrl(_))
+    ));
+}
+
+// Checking that a custom transport can be plugged in, and that URLs are joined properly.
+#[test]
+fn test_custom_transport() {
+    struct Echo;
+    impl Transport for Echo {
+        fn get(&self, url: &str, _headers: &[(&str, &str)]) -> Result<Response, DownloadError> {
+            Ok(Response {
+                status: 200,
+                headers: vec![("content-length".to_string(), url.len().to_string())],
+                body: url.as_bytes().to_vec(),
+            })
+        }
+    }
+
+    let client = Client::with_transport("http://example.com/odp", Echo);
+    assert_eq!(client.base_url(), "http://example.com/odp/");
+    assert_eq!(
+        client.download_with_path("/10_minutes/now/").unwrap(),
+        b"http://example.com/odp/10_minutes/now/",
+    );
+    assert_eq!(Client::new().url(LATEST_PATH), format!("{}{}", ODP_ROOT, LATEST_PATH));
+}
+
+pub(crate) const TEST_LISTING_NOW: &str = include_str!("../../tests/listing_10_minutes_now.html");
+pub(crate) const TEST_LISTING_HISTORICAL: &str =
+    include_str!("../../tests/listing_10_minutes_historical.html");
+
+// Checking the plain listing layout, with the directories and escaped names.
+#[test]
+fn test_parse_listing() {
+    let entries = parse_listing(TEST_LISTING_NOW);
+    let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, [
+        "old",
+        "HABP_10M_SYNOP_20250110134805.csv.zip",
+        "HABP_10M_SYNOP_20250110135805.csv.zip",
+        "HABP_10M_SYNOP_20250110140805.csv.zip",
+        "HABP_10M_SYNOP_LATEST.csv.zip",
+        "Leírás (HU).pdf",
+    ]);
+    assert_eq!(entries[0], ListingEntry {
+        name: "old".to_string(),
+        size: None,
+        modified: Timestamp::new(2025, 1, 2, 8, 0).ok(),
+        is_dir: true,
+    });
+    assert_eq!(entries[3], ListingEntry {
+        name: "HABP_10M_SYNOP_20250110140805.csv.zip".to_string(),
+        size: Some(18021),
+        modified: Timestamp::new(2025, 1, 10, 14, 8).ok(),
+        is_dir: false,
+    });
+
+    assert!(parse_listing(TEST_CSV).is_empty());
+    assert!(parse_listing("<a href=\"x.zip\">x.zip</a> yesterday").iter().all(|e| {
+        e.name == "x.zip" && e.size.is_none() && e.modified.is_none()
+    }));
+}
+
+// Checking the table layout, with the rounded sizes.
+#[test]
+fn test_parse_listing_table() {
+    let entries = parse_listing(TEST_LISTING_HISTORICAL);
+    assert_eq!(entries.len(), 5);
+    assert!(entries.iter().all(|e| !e.is_dir && e.modified.is_some()));
+    assert_eq!(entries[0].name, "HABP_10M_13704_20050801_20151231_hist.zip");
+    assert_eq!(entries[0].size, Some(18 * 1024 * 1024));
+    assert_eq!(entries[0].modified, Timestamp::new(2024, 2, 12, 10, 21).ok());
+    assert_eq!(entries[3].size, Some(6656));
+    assert_eq!(entries[4].size, Some(912));
+}
+
+// Checking the newest file by pattern, also through the stand-in server.
+#[test]
+fn test_newest_matching() {
+    let entries = parse_listing(TEST_LISTING_NOW);
+    let newest = |pattern| newest_matching(&entries, pattern).map(|e| e.name.as_str());
+    // Same time as the latest one, the name decides
+    assert_eq!(newest("HABP_10M_SYNOP_*.csv.zip"), Some("HABP_10M_SYNOP_LATEST.csv.zip"));
+    assert_eq!(newest("HABP_10M_SYNOP_2*.csv.zip"), Some("HABP_10M_SYNOP_20250110140805.csv.zip"));
+    assert_eq!(newest("*.pdf"), Some("Leírás (HU).pdf"));
+    assert_eq!(newest("old"), None);
+    assert_eq!(newest("HABP_1H_*"), None);
+
+    let (base_url, handle) = serve(vec![response(200, &[], TEST_LISTING_HISTORICAL.as_bytes())]);
+    let client = Client::with_transport(&base_url, UreqTransport::new());
+    let newest = client.find_newest("10_minutes/historical", "HABP_10M_13704_*_hist.zip");
+    assert_eq!(
+        newest.unwrap().map(|e| e.name),
+        Some("HABP_10M_13704_20160101_20231231_hist.zip".to_string())
+    );
+    let requests = handle.join().unwrap();
+    assert!(requests[0].starts_with("GET /10_minutes/historical/ HTTP/1.1\r\n"));
+}
+
+// Checking the conditional requests with the cache, and the not modified result.
+#[test]
+fn test_download_cache() {
+    let dir = std::env::temp_dir().join(format!("odp-cache-test-{}", std::process::id()));
+    let etag = [("ETag", "\"abc\""), ("Last-Modified", "Fri, 10 Jan 2025 14:08:05 GMT")];
+    let (base_url, handle) = serve(vec![
+        response(200, &etag, TEST_ZIP),
+        response(304, &[], b""),
+        response(200, &[("ETag", "\"def\"")], b"changed"),
+        response(200, &[], b"unvalidated"),
+        response(200, &etag, TEST_ZIP),
+    ]);
+    let cache = DownloadCache::new(&dir);
+    let client =
+        Client::with_transport(&base_url, UreqTransport::new()).with_cache(cache.clone());
+    let url = client.url(LATEST_PATH);
+    assert_eq!(cache.body(&url).unwrap(), None);
+
+    let body = TEST_ZIP.to_vec();
+    assert_eq!(client.download_latest_if_modified().unwrap(), Conditional::Modified(body.clone()));
+    assert_eq!(client.download_latest_if_modified().unwrap(), Conditional::NotModified(body));
+    assert_eq!(client.download_latest().unwrap(), b"changed");
+    assert_eq!(cache.body(&url).unwrap().as_deref(), Some(&b"changed"[..]));
+    // Responses without the headers are not stored
+    assert_eq!(client.download_with_path("other.zip").unwrap(), b"unvalidated");
+    assert_eq!(cache.body(&client.url("other.zip")).unwrap(), None);
+    // Without the cached body, the request is not conditional
+    let key = DownloadCache::key(&url);
+    std::fs::remove_file(dir.join(format!("{}.body", key))).unwrap();
+    let body = TEST_ZIP.to_vec();
+    assert_eq!(client.download_latest_if_modified().unwrap(), Conditional::Modified(body.clone()));
+    assert_eq!(cache.body(&url).unwrap(), Some(body));
+
+    let requests = handle.join().unwrap();
+    assert!(!requests[0].contains("If-None-Match"));
+    assert!(requests[1].contains("\r\nIf-None-Match: \"abc\"\r\n"));
+    assert!(requests[1].contains("\r\nIf-Modified-Since: Fri, 10 Jan 2025 14:08:05 GMT\r\n"));
+    assert!(requests[2].contains("\r\nIf-None-Match: \"abc\"\r\n"));
+    assert!(!requests[3].contains("If-"));
+    assert!(!requests[4].contains("If-"));
+
+    assert!(dir.join(format!("{}.body", key)).exists());
+    assert_eq!(DownloadCache::key(""), "cbf29ce484222325");
+    assert_eq!(DownloadCache::key("a"), "af63dc4c8601ec8c");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -8,9 +8,13 @@
 
 //! All the custom error types used in the crate.
 
+use crate::field::Field;
+
 use std::error::Error;
 use std::fmt;
 use std::fmt::Display;
+use std::io;
+use std::num::{ParseFloatError, ParseIntError};
 
 //{ `field` module
 
@@ -29,7 +33,85 @@ impl Display for ParseFieldError {
     }
 }
 
-pub struct ParseFieldFilterError;
+/// Reason of a failure in `ParseFieldFilterError`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FieldFilterErrorKind {
+    Empty,
+    UnknownField(ParseFieldError),
+    IndexOutOfRange,
+    InvalidRange,
+    NoMatch,
+    /// A range end is not a column of the dataset.
+    NotInDataset,
+}
+
+/// Error type used in `FieldFilter::from_strs`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseFieldFilterError {
+    pub(crate) item: String,
+    pub(crate) position: usize,
+    pub(crate) exclude: bool,
+    pub(crate) kind: FieldFilterErrorKind,
+}
+impl ParseFieldFilterError {
+    /// The offending item of the list.
+    pub fn item(&self) -> &str {
+        &self.item
+    }
+
+    /// Byte offset of the item in the list.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Whether the error is in the exclude list (as opposed to the include list).
+    pub fn exclude(&self) -> bool {
+        self.exclude
+    }
+
+    /// Why the item could not be parsed.
+    pub fn kind(&self) -> &FieldFilterErrorKind {
+        &self.kind
+    }
+}
+impl Error for ParseFieldFilterError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.kind {
+            FieldFilterErrorKind::UnknownField(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+impl Display for ParseFieldFilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg: String = match &self.kind {
+            FieldFilterErrorKind::Empty => "empty item".to_string(),
+            FieldFilterErrorKind::UnknownField(e) => e.to_string(),
+            FieldFilterErrorKind::IndexOutOfRange => "column index out of range".to_string(),
+            FieldFilterErrorKind::InvalidRange => "range end precedes start".to_string(),
+            FieldFilterErrorKind::NoMatch => "pattern matches no fields".to_string(),
+            FieldFilterErrorKind::NotInDataset => "field is not in the dataset".to_string(),
+        };
+        write!(
+            f,
+            "invalid item \"{}\" at position {} of the {} list, {}",
+            self.item,
+            self.position,
+            if self.exclude { "exclude" } else { "include" },
+            msg,
+        )
+    }
+}
+
+/// Error type used in `Dataset::try_from`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseDatasetError(pub(crate) String);
+impl Error for ParseDatasetError {}
+impl Display for ParseDatasetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid dataset \"{}\", expected 10_minutes, hourly or daily", self.0)
+    }
+}
 //}
 
 //{ `format` module
@@ -81,6 +163,8 @@ pub enum ParseHeaderError {
     InvalidEorPosition,
     InvalidTimeAlignment,
     InvalidDelimiter(InvalidDelimiterSource),
+    /// The fields do not all belong to one of the datasets.
+    UnknownDataset,
 }
 impl Error for ParseHeaderError {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
@@ -105,6 +189,7 @@ impl Display for ParseHeaderError {
                 InvalidDelimiterSource::Error(e) => e.to_string(),
                 InvalidDelimiterSource::String(s) => format!("invalid delimiter found \"{}\"", s),
             },
+            Self::UnknownDataset => "fields do not belong to a single dataset".to_string(),
         };
         write!(f, "failed to parse header, {}", msg)
     }
@@ -113,17 +198,888 @@ impl Display for ParseHeaderError {
 
 //{ `record` module
 
-pub struct ParseRecordError;
-pub struct ParseRecordFilterError;
-pub struct TryFromRawRecordError;
+/// Error type used in `RawRecord::from_csv`.
+///
+/// Columns are character positions in the line, starting from 1.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParseRecordError {
+    /// `column` is where the first extra value starts, or the end of the line if values are
+    /// missing.
+    FieldCount { expected: usize, found: usize, column: usize },
+    /// The delimiter after `field` is not found where expected (at `column`).
+    Misaligned { field: Field, column: usize },
+    /// `column` is where the value of `field` starts.
+    MissingMandatory { field: Field, column: usize },
+}
+impl ParseRecordError {
+    /// The offending field, if the problem is specific to one.
+    pub fn field(&self) -> Option<Field> {
+        match self {
+            Self::FieldCount { .. } => None,
+            Self::Misaligned { field, .. } | Self::MissingMandatory { field, .. } => Some(*field),
+        }
+    }
+
+    /// Position of the problem in the line.
+    pub fn column(&self) -> usize {
+        match self {
+            Self::FieldCount { column, .. }
+            | Self::Misaligned { column, .. }
+            | Self::MissingMandatory { column, .. } => *column,
+        }
+    }
+}
+impl Error for ParseRecordError {}
+impl Display for ParseRecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg: String = match self {
+            Self::FieldCount {
+                expected,
+                found,
+                column,
+            } => format!("expected {} fields, found {} (column {})", expected, found, column),
+            Self::Misaligned { field, column } => {
+                format!("field `{}` is not aligned, expected delimiter at column {}", field, column)
+            }
+            Self::MissingMandatory { field, column } => {
+                format!("missing value for `{}` at column {}", field, column)
+            }
+        };
+        write!(f, "failed to parse record, {}", msg)
+    }
+}
+
+/// Error type used in `RecordFilter::from_str`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseRecordFilterError(pub(crate) usize);
+impl ParseRecordFilterError {
+    pub fn new(position: usize) -> Self {
+        Self(position)
+    }
+}
+impl Error for ParseRecordFilterError {}
+impl Display for ParseRecordFilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "empty item in station list at position {}", self.0)
+    }
+}
+
+/// Reason of a failed value conversion, used in `TryFromRawRecordError`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParseValueError {
+    Float(ParseFloatError),
+    /// `NaN` or infinity, which `f64::from_str` accepts but ODP never uses.
+    NonFinite,
+    Integer(ParseIntError),
+    Timestamp(ParseTimestampError),
+}
+impl Error for ParseValueError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Float(e) => Some(e),
+            Self::NonFinite => None,
+            Self::Integer(e) => Some(e),
+            Self::Timestamp(e) => Some(e),
+        }
+    }
+}
+impl Display for ParseValueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Float(e) => e.fmt(f),
+            Self::NonFinite => write!(f, "not a finite number"),
+            Self::Integer(e) => e.fmt(f),
+            Self::Timestamp(e) => e.fmt(f),
+        }
+    }
+}
+
+/// Error type used in the `MetRecord` conversion from `RawRecord`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TryFromRawRecordError {
+    pub(crate) line: Option<usize>,
+    pub(crate) station_number: String,
+    pub(crate) field: Field,
+    pub(crate) value: String,
+    pub(crate) reason: ParseValueError,
+}
+impl TryFromRawRecordError {
+    /// Number of the input line of the record, if known (e.g. when read by `convert_csv_into`).
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+
+    /// Sets the line number, for the callers that know it.
+    pub fn at_line(self, line: usize) -> Self {
+        Self {
+            line: Some(line),
+            ..self
+        }
+    }
+
+    /// Station of the record that failed to convert.
+    pub fn station_number(&self) -> &str {
+        &self.station_number
+    }
+
+    /// The field that failed to convert.
+    pub fn field(&self) -> Field {
+        self.field
+    }
+
+    /// The offending value.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Why the value could not be converted.
+    pub fn reason(&self) -> &ParseValueError {
+        &self.reason
+    }
+}
+impl Error for TryFromRawRecordError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.reason)
+    }
+}
+impl Display for TryFromRawRecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to convert record of station {}", self.station_number)?;
+        if let Some(line) = self.line {
+            write!(f, " at line {}", line)?;
+        }
+        write!(f, ", invalid value \"{}\" for `{}`: {}", self.value, self.field, self.reason)
+    }
+}
+//}
+
+//{ `timestamp` module
+
+/// Error type used in `Timestamp::from_str` and `Timestamp::new`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParseTimestampError {
+    InvalidFormat(String),
+    InvalidDate(String),
+}
+impl Error for ParseTimestampError {}
+impl Display for ParseTimestampError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidFormat(s) => {
+                write!(f, "expected `YYYYMMDDhhmm` or `YYYYMMDD` timestamp, found \"{}\"", s)
+            }
+            Self::InvalidDate(s) => write!(f, "date or time does not exist \"{}\"", s),
+        }
+    }
+}
 //}
 
 //{ `csv` module
 
-pub struct ReadCsvError;
-pub struct WriteCsvError;
+/// Error type used in `read_csv` and `validate_csv`.
+///
+/// Line numbers start from 1 (which is the header), byte offsets from 0 (which is the start of
+/// the header), both refer to the line being read.
+#[derive(Debug)]
+pub enum ReadCsvError {
+    Io { line: usize, source: io::Error },
+    EmptyInput,
+    Header(ParseHeaderError),
+    Record { line: usize, offset: u64, source: ParseRecordError },
+}
+impl ReadCsvError {
+    /// Number of the offending line, if the problem is specific to one.
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            Self::Io { line, .. } | Self::Record { line, .. } => Some(*line),
+            Self::Header(_) => Some(1),
+            Self::EmptyInput => None,
+        }
+    }
+}
+impl Error for ReadCsvError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::EmptyInput => None,
+            Self::Header(e) => Some(e),
+            Self::Record { source, .. } => Some(source),
+        }
+    }
+}
+impl Display for ReadCsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io { line, .. } => write!(f, "failed to read CSV at line {}", line),
+            Self::EmptyInput => write!(f, "failed to read CSV, input is empty"),
+            Self::Header(_) => write!(f, "failed to read CSV header"),
+            Self::Record { line, offset, .. } => {
+                write!(f, "failed to read CSV at line {} (byte {})", line, offset)
+            }
+        }
+    }
+}
+
+/// Error type used in `write_csv`.
+#[derive(Debug)]
+pub struct WriteCsvError {
+    pub(crate) line: usize,
+    pub(crate) source: io::Error,
+}
+impl WriteCsvError {
+    /// Number of the output line being written, starting from 1 (which is the header).
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+impl Error for WriteCsvError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.source)
+    }
+}
+impl Display for WriteCsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to write CSV at line {}", self.line)
+    }
+}
+
+/// Error type used in `CsvAppender::new`.
+#[derive(Debug)]
+pub enum AppendCsvError {
+    Io(io::Error),
+    Read(ReadCsvError),
+    /// The header of the existing content does not match the format, both are included as
+    /// header lines.
+    HeaderMismatch { expected: String, found: String },
+}
+impl Error for AppendCsvError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Read(e) => Some(e),
+            Self::HeaderMismatch { .. } => None,
+        }
+    }
+}
+impl Display for AppendCsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(_) => write!(f, "failed to open CSV for appending"),
+            Self::Read(_) => write!(f, "failed to read existing CSV content"),
+            Self::HeaderMismatch { expected, found } => write!(
+                f,
+                "header of existing CSV content does not match, expected \"{}\", found \"{}\"",
+                expected, found
+            ),
+        }
+    }
+}
+impl From<io::Error> for AppendCsvError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+impl From<ReadCsvError> for AppendCsvError {
+    fn from(e: ReadCsvError) -> Self {
+        Self::Read(e)
+    }
+}
+
+/// Error type used in `convert_csv`.
+#[derive(Debug)]
 pub enum CsvError {
-    ReadErr,
-    WriteErr,
+    ReadErr(ReadCsvError),
+    WriteErr(WriteCsvError),
+}
+impl Error for CsvError {
+    /// Transparent, the `Display` is also that of the wrapped error.
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::ReadErr(e) => e.source(),
+            Self::WriteErr(e) => e.source(),
+        }
+    }
+}
+impl Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ReadErr(e) => e.fmt(f),
+            Self::WriteErr(e) => e.fmt(f),
+        }
+    }
+}
+impl From<ReadCsvError> for CsvError {
+    fn from(e: ReadCsvError) -> Self {
+        Self::ReadErr(e)
+    }
+}
+impl From<WriteCsvError> for CsvError {
+    fn from(e: WriteCsvError) -> Self {
+        Self::WriteErr(e)
+    }
+}
+//}
+
+//{ `sql` module
+
+/// Error type used in `Dialect::try_from`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseDialectError(pub(crate) String);
+impl Error for ParseDialectError {}
+impl Display for ParseDialectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown SQL dialect \"{}\", expected sqlite or postgres", self.0)
+    }
+}
+
+/// Error type used in `SqlWriter` and `write_sql`.
+#[derive(Debug)]
+pub enum WriteSqlError {
+    Io(io::Error),
+    /// Values are converted through `MetRecord`, so that only valid literals are written.
+    Convert(TryFromRawRecordError),
+}
+impl Error for WriteSqlError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Convert(e) => Some(e),
+        }
+    }
+}
+impl Display for WriteSqlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(_) => write!(f, "failed to write SQL"),
+            Self::Convert(_) => write!(f, "failed to write SQL, invalid record"),
+        }
+    }
+}
+impl From<io::Error> for WriteSqlError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+impl From<TryFromRawRecordError> for WriteSqlError {
+    fn from(e: TryFromRawRecordError) -> Self {
+        Self::Convert(e)
+    }
+}
+//}
+
+//{ `copy` module
+
+/// Error type used in `CopyWriter` and `write_copy`.
+#[derive(Debug)]
+pub struct WriteCopyError {
+    pub(crate) line: usize,
+    pub(crate) source: io::Error,
+}
+impl WriteCopyError {
+    /// Number of the data line being written, starting from 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+impl Error for WriteCopyError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.source)
+    }
+}
+impl Display for WriteCopyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to write COPY data at line {}", self.line)
+    }
+}
+//}
+
+//{ `sink` module
+
+/// Error type used in `SinkKind::try_from`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseSinkKindError(pub(crate) String);
+impl Error for ParseSinkKindError {}
+impl Display for ParseSinkKindError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown output kind \"{}\"", self.0)
+    }
+}
+
+/// Error type used in `RecordSink` and `convert_csv_into`.
+#[derive(Debug)]
+pub enum SinkError {
+    Read(ReadCsvError),
+    Convert(TryFromRawRecordError),
+    Csv(WriteCsvError),
+    Append(AppendCsvError),
+    Sql(WriteSqlError),
+    Copy(WriteCopyError),
+    #[cfg(feature = "sqlite")]
+    Db(DbError),
+    Io(io::Error),
+}
+impl Error for SinkError {
+    /// Transparent, the `Display` is also that of the wrapped error.
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Read(e) => e.source(),
+            Self::Convert(e) => e.source(),
+            Self::Csv(e) => e.source(),
+            Self::Append(e) => e.source(),
+            Self::Sql(e) => e.source(),
+            Self::Copy(e) => e.source(),
+            #[cfg(feature = "sqlite")]
+            Self::Db(e) => e.source(),
+            Self::Io(e) => e.source(),
+        }
+    }
+}
+impl Display for SinkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Read(e) => e.fmt(f),
+            Self::Convert(e) => e.fmt(f),
+            Self::Csv(e) => e.fmt(f),
+            Self::Append(e) => e.fmt(f),
+            Self::Sql(e) => e.fmt(f),
+            Self::Copy(e) => e.fmt(f),
+            #[cfg(feature = "sqlite")]
+            Self::Db(e) => e.fmt(f),
+            Self::Io(e) => e.fmt(f),
+        }
+    }
+}
+impl From<ReadCsvError> for SinkError {
+    fn from(e: ReadCsvError) -> Self {
+        Self::Read(e)
+    }
+}
+impl From<TryFromRawRecordError> for SinkError {
+    fn from(e: TryFromRawRecordError) -> Self {
+        Self::Convert(e)
+    }
+}
+impl From<WriteCsvError> for SinkError {
+    fn from(e: WriteCsvError) -> Self {
+        Self::Csv(e)
+    }
+}
+impl From<AppendCsvError> for SinkError {
+    fn from(e: AppendCsvError) -> Self {
+        Self::Append(e)
+    }
+}
+impl From<WriteSqlError> for SinkError {
+    fn from(e: WriteSqlError) -> Self {
+        Self::Sql(e)
+    }
+}
+impl From<WriteCopyError> for SinkError {
+    fn from(e: WriteCopyError) -> Self {
+        Self::Copy(e)
+    }
+}
+#[cfg(feature = "sqlite")]
+impl From<DbError> for SinkError {
+    fn from(e: DbError) -> Self {
+        Self::Db(e)
+    }
+}
+impl From<io::Error> for SinkError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+//}
+
+//{ `archive` module
+
+/// Error type used in `ArchiveDownloader`.
+#[derive(Debug)]
+pub enum ArchiveError {
+    Download(DownloadError),
+    /// The server sent a different part of the file than requested.
+    UnexpectedRange {
+        url: String,
+        start: u64,
+        content_range: String,
+    },
+    Io {
+        path: String,
+        source: io::Error,
+    },
+    Zip {
+        path: String,
+        source: ZipError,
+    },
+    /// The CSV file in the archive (named `name`) is invalid.
+    Csv {
+        name: String,
+        source: ReadCsvError,
+    },
+}
+impl Error for ArchiveError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Download(e) => e.source(),
+            Self::UnexpectedRange { .. } => None,
+            Self::Io { source, .. } => Some(source),
+            Self::Zip { source, .. } => Some(source),
+            Self::Csv { source, .. } => Some(source),
+        }
+    }
+}
+impl Display for ArchiveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Download(e) => e.fmt(f),
+            Self::UnexpectedRange {
+                url,
+                start,
+                content_range,
+            } => write!(
+                f,
+                "requested {} from byte {}, received range \"{}\"",
+                url, start, content_range
+            ),
+            Self::Io { path, .. } => write!(f, "failed to access \"{}\"", path),
+            Self::Zip { path, .. } => write!(f, "failed to unzip \"{}\"", path),
+            Self::Csv { name, .. } => write!(f, "invalid CSV file \"{}\" in the archive", name),
+        }
+    }
+}
+impl From<DownloadError> for ArchiveError {
+    fn from(e: DownloadError) -> Self {
+        Self::Download(e)
+    }
+}
+//}
+
+//{ `config` module
+
+/// Error type used in `Config::from_str`, every variant has the number of the offending line.
+#[derive(Debug)]
+pub enum ParseConfigError {
+    /// The line is neither a section header nor a `key = value` pair in a section.
+    Syntax { line: usize },
+    UnknownSection { line: usize, name: String },
+    /// The key is given with its section, as `section.key`.
+    UnknownKey { line: usize, key: String },
+    InvalidValue {
+        line: usize,
+        key: String,
+        source: Box<dyn Error + Send + Sync>,
+    },
+}
+impl ParseConfigError {
+    /// Returns the line number (starting from 1).
+    pub fn line(&self) -> usize {
+        match self {
+            Self::Syntax { line }
+            | Self::UnknownSection { line, .. }
+            | Self::UnknownKey { line, .. }
+            | Self::InvalidValue { line, .. } => *line,
+        }
+    }
+}
+impl Error for ParseConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::InvalidValue { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+impl Display for ParseConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax { line } => {
+                write!(f, "expected a section header or key = value at line {}", line)
+            }
+            Self::UnknownSection { line, name } => {
+                write!(f, "unknown section \"{}\" at line {}", name, line)
+            }
+            Self::UnknownKey { line, key } => write!(f, "unknown key \"{}\" at line {}", key, line),
+            Self::InvalidValue { line, key, .. } => {
+                write!(f, "invalid value for \"{}\" at line {}", key, line)
+            }
+        }
+    }
+}
+
+/// Error type used in `TeeConfig::from_str`.
+#[derive(Debug)]
+pub enum ParseTeeError {
+    /// Not in the form of `KIND[,OPTION=VALUE...]:FILE`.
+    Syntax(String),
+    Kind(ParseSinkKindError),
+    UnknownOption(String),
+    InvalidValue {
+        option: String,
+        source: Box<dyn Error + Send + Sync>,
+    },
+}
+impl Error for ParseTeeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Kind(e) => e.source(),
+            Self::InvalidValue { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+impl Display for ParseTeeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax(s) => write!(f, "expected KIND[,OPTION=VALUE...]:FILE, found \"{}\"", s),
+            Self::Kind(e) => e.fmt(f),
+            Self::UnknownOption(option) => write!(f, "unknown output option \"{}\"", option),
+            Self::InvalidValue { option, .. } => {
+                write!(f, "invalid value for output option \"{}\"", option)
+            }
+        }
+    }
+}
+impl From<ParseSinkKindError> for ParseTeeError {
+    fn from(e: ParseSinkKindError) -> Self {
+        Self::Kind(e)
+    }
+}
+
+/// Error type used in `Config::load`, transparent for parsing errors.
+#[derive(Debug)]
+pub enum ConfigError {
+    Io { path: String, source: io::Error },
+    Parse(ParseConfigError),
+}
+impl Error for ConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::Parse(e) => e.source(),
+        }
+    }
+}
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io { path, .. } => write!(f, "failed to read configuration file \"{}\"", path),
+            Self::Parse(e) => e.fmt(f),
+        }
+    }
+}
+impl From<ParseConfigError> for ConfigError {
+    fn from(e: ParseConfigError) -> Self {
+        Self::Parse(e)
+    }
+}
+//}
+
+//{ `daemon` module
+
+/// Error type used in `Daemon::poll`, transparent for the inner errors.
+#[derive(Debug)]
+pub enum DaemonError {
+    Download(DownloadError),
+    Zip(ZipError),
+    Sink(SinkError),
+}
+impl Error for DaemonError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Download(e) => e.source(),
+            Self::Zip(e) => e.source(),
+            Self::Sink(e) => e.source(),
+        }
+    }
+}
+impl Display for DaemonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Download(e) => e.fmt(f),
+            Self::Zip(e) => e.fmt(f),
+            Self::Sink(e) => e.fmt(f),
+        }
+    }
+}
+impl From<DownloadError> for DaemonError {
+    fn from(e: DownloadError) -> Self {
+        Self::Download(e)
+    }
+}
+impl From<ZipError> for DaemonError {
+    fn from(e: ZipError) -> Self {
+        Self::Zip(e)
+    }
+}
+impl From<SinkError> for DaemonError {
+    fn from(e: SinkError) -> Self {
+        Self::Sink(e)
+    }
+}
+//}
+
+//{ `download` module
+
+/// Error type used in `Client` and `Transport`.
+#[derive(Debug)]
+pub enum DownloadError {
+    InvalidUrl(String),
+    Connect {
+        url: String,
+        source: Box<dyn Error + Send + Sync>,
+    },
+    Status {
+        url: String,
+        status: u16,
+    },
+    Truncated {
+        url: String,
+        expected: u64,
+        received: u64,
+    },
+    Io {
+        url: String,
+        source: io::Error,
+    },
+    /// Connecting, or waiting for the response, took longer than allowed (see
+    /// `UreqTransport::with_timeouts`).
+    Timeout(String),
+    /// A file of the `DownloadCache` cannot be read or written.
+    Cache {
+        path: String,
+        source: io::Error,
+    },
+}
+impl Error for DownloadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Connect { source, .. } => Some(source.as_ref()),
+            Self::Io { source, .. } | Self::Cache { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+impl Display for DownloadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidUrl(url) => write!(f, "invalid URL \"{}\"", url),
+            Self::Connect { url, .. } => write!(f, "failed to connect to {}", url),
+            Self::Status { url, status } => write!(f, "HTTP status {} for {}", status, url),
+            Self::Truncated {
+                url,
+                expected,
+                received,
+            } => write!(
+                f,
+                "truncated download from {}, received {} of {} bytes",
+                url, received, expected
+            ),
+            Self::Io { url, .. } => write!(f, "failed to read response from {}", url),
+            Self::Timeout(url) => write!(f, "timed out waiting for {}", url),
+            Self::Cache { path, .. } => write!(f, "failed to access cache file \"{}\"", path),
+        }
+    }
+}
+//}
+
+//{ `zip` module
+
+/// Error type used in `unzip` and `zip`.
+#[derive(Debug)]
+pub enum ZipError {
+    Io(io::Error),
+    Corrupt(zip::result::ZipError),
+    MemberCount(usize),
+    UnsupportedCompression(String),
+}
+impl Error for ZipError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Corrupt(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+impl Display for ZipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "zip I/O error, {}", e),
+            Self::Corrupt(e) => write!(f, "invalid zip archive, {}", e),
+            Self::MemberCount(n) => write!(f, "expected a single file in zip archive, found {}", n),
+            Self::UnsupportedCompression(s) => {
+                write!(f, "unsupported zip compression method {}", s)
+            }
+        }
+    }
+}
+impl From<zip::result::ZipError> for ZipError {
+    /// I/O errors are passed through, everything else counts as `Corrupt`.
+    fn from(e: zip::result::ZipError) -> Self {
+        match e {
+            zip::result::ZipError::Io(e) => Self::Io(e),
+            e => Self::Corrupt(e),
+        }
+    }
+}
+//}
+
+//{ `db` module
+
+/// Error type used in `Database`.
+#[cfg(feature = "sqlite")]
+#[derive(Debug)]
+pub enum DbError {
+    Sqlite(rusqlite::Error),
+    MissingColumn(Field),
+    ColumnType {
+        field: Field,
+        expected: &'static str,
+        found: String,
+    },
+    /// The primary key of the existing table is not (`Time`, `StationNumber`), the columns found
+    /// are included.
+    PrimaryKey(Vec<String>),
+}
+#[cfg(feature = "sqlite")]
+impl Error for DbError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Sqlite(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+#[cfg(feature = "sqlite")]
+impl Display for DbError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Sqlite(e) => write!(f, "database error, {}", e),
+            Self::MissingColumn(field) => {
+                write!(f, "incompatible `data` table, missing column for `{}`", field)
+            }
+            Self::ColumnType {
+                field,
+                expected,
+                found,
+            } => write!(
+                f,
+                "incompatible `data` table, column `{}` has type {}, expected {}",
+                field, found, expected
+            ),
+            Self::PrimaryKey(columns) => write!(
+                f,
+                "incompatible `data` table, expected primary key (Time, StationNumber), found ({})",
+                columns.join(", ")
+            ),
+        }
+    }
+}
+#[cfg(feature = "sqlite")]
+impl From<rusqlite::Error> for DbError {
+    fn from(e: rusqlite::Error) -> Self {
+        Self::Sqlite(e)
+    }
 }
 //}
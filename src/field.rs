@@ -19,12 +19,16 @@ use std::fmt::Display;
 pub(crate) mod field_properties;
 
 #[cfg(test)]
+#[allow(clippy::needless_range_loop)]
 pub(crate) mod test;
 
 /// Stores a list of selected CSV `Field`s.
 ///
 /// For reading operations, this will represent fields that must be present in the input,
 /// while for writing it will be used to apply column filtering.
+///
+/// With the `serde` feature enabled, it is (de)serialized as a list of `Field`s, checked the same
+/// way as a header.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct FieldConfig {
     fields: Vec<Field>,
@@ -35,7 +39,19 @@ impl FieldConfig {
         Self::new(true, true, true, true, None)
     }
 
-    /// Constructs an instance by selecting field types and supplying a filter definition.
+    /// Constructs an instance for the 10-minute dataset, see `new_for`.
+    pub fn new(
+        info: bool,
+        values: bool,
+        q: bool,
+        eor: bool,
+        filter: Option<&FieldFilter>,
+    ) -> Self {
+        Self::new_for(Dataset::TenMinute, info, values, q, eor, filter)
+    }
+
+    /// Constructs an instance by selecting field types of a dataset and supplying a filter
+    /// definition. Fields not in the dataset are ignored by the filter.
     ///
     /// Fields `Time` and `StationNumber` are mandatory and will always be included.
     ///
@@ -46,7 +62,8 @@ impl FieldConfig {
     /// - `q`: For every value field there is a matching Q-field that is reserved for development
     ///   purposes by Met (and generally empty).
     /// - `eor`: "End Of Record" marker column at the end of every line, having the value `EOR`.
-    pub fn new(
+    pub fn new_for(
+        dataset: Dataset,
         info: bool,
         values: bool,
         q: bool,
@@ -54,8 +71,10 @@ impl FieldConfig {
         filter: Option<&FieldFilter>,
     ) -> Self {
         Self {
-            fields: FIELD_ARRAY
-                .into_iter()
+            fields: dataset
+                .fields()
+                .iter()
+                .map(|f| &FIELD_ARRAY[*f as usize])
                 .filter(|f| {
                     let pre = match f.field_type {
                         FieldType::Mandatory => true,
@@ -95,6 +114,19 @@ impl FieldConfig {
             }
         }
 
+        Self::from_fields(fields)
+    }
+
+    /// Constructs an instance from a field list, checking it the same way as `from_header`.
+    ///
+    /// The fields must all belong to one of the datasets, see `Dataset::detect`.
+    pub fn from_fields(fields: Vec<Field>) -> Result<Self, ParseHeaderError> {
+        for (i, f) in fields.iter().enumerate() {
+            if fields[..i].contains(f) {
+                return Err(ParseHeaderError::FoundDuplicates(f.title().to_string()));
+            }
+        }
+
         match fields.first() {
             Some(f) if *f == Field::Time => (),
             _ => return Err(ParseHeaderError::MissingTime),
@@ -111,9 +143,18 @@ impl FieldConfig {
             _ => (),
         }
 
+        if Dataset::detect(&fields).is_none() {
+            return Err(ParseHeaderError::UnknownDataset);
+        }
+
         Ok(Self { fields })
     }
 
+    /// Returns the dataset the fields belong to.
+    pub fn dataset(&self) -> Dataset {
+        Dataset::detect(&self.fields).unwrap_or_default()
+    }
+
     /// Returns an iterator of the field list.
     pub fn fields(&self) -> impl Iterator<Item = &Field> {
         self.fields.iter()
@@ -125,6 +166,20 @@ impl Default for FieldConfig {
         Self::new_with_all()
     }
 }
+#[cfg(feature = "serde")]
+impl serde::Serialize for FieldConfig {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.fields.serialize(serializer)
+    }
+}
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for FieldConfig {
+    /// Fails the same way as `from_fields`.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let fields = Vec::<Field>::deserialize(deserializer)?;
+        Self::from_fields(fields).map_err(serde::de::Error::custom)
+    }
+}
 
 /// Represents two sets, one to *include* and one to *exclude* fields in a `FieldConfig`.
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -152,15 +207,138 @@ impl FieldFilter {
     }
 
     /// Tries to parse two comma-separated lists, supplied as strings (e.g. by the user).
+    ///
+    /// An item in the list can be:
+    /// - a field title, e.g. `t`;
+    /// - a column index, starting from 1 as in the ODP files, e.g. `9` (which is also `t`);
+    /// - a range of titles or indices, with `-` or `..` in between, e.g. `6-9` or `fx..fxs`;
+    /// - a title pattern with `*` wildcards, e.g. `Q_*`.
+    ///
+    /// Indices, ranges and patterns refer to the columns of the 10-minute dataset, see
+    /// `from_strs_for` for the others.
+    ///
+    /// Returns `None` if both lists are empty.
     pub fn from_strs(
-        _include_str: Option<&str>,
-        _exclude_str: Option<&str>,
+        include_str: Option<&str>,
+        exclude_str: Option<&str>,
+    ) -> Result<Option<Self>, ParseFieldFilterError> {
+        Self::from_strs_for(Dataset::TenMinute, include_str, exclude_str)
+    }
+
+    /// Same as `from_strs`, with indices, ranges and patterns referring to the columns of
+    /// `dataset`.
+    pub fn from_strs_for(
+        dataset: Dataset,
+        include_str: Option<&str>,
+        exclude_str: Option<&str>,
     ) -> Result<Option<Self>, ParseFieldFilterError> {
-        todo!()
+        let schema = dataset.fields();
+        let including = match include_str {
+            Some(s) => parse_field_list(schema, s, false)?,
+            None => Vec::new(),
+        };
+        let excluding = match exclude_str {
+            Some(s) => parse_field_list(schema, s, true)?,
+            None => Vec::new(),
+        };
+        Ok(Self::new(including, excluding))
     }
 }
 
-/// All the columns in an unmodified source CSV downloaded from ODP,
+/// Parses a list for `FieldFilter::from_strs_for` against the columns in `schema`, `exclude` is
+/// only used for error reporting.
+fn parse_field_list(
+    schema: &[Field],
+    list: &str,
+    exclude: bool,
+) -> Result<Vec<Field>, ParseFieldFilterError> {
+    let mut fields: Vec<Field> = Vec::new();
+    if list.trim().is_empty() {
+        return Ok(fields);
+    }
+
+    let mut position = 0;
+    for token in list.split(',') {
+        let item = token.trim();
+        let item_position = position + (token.len() - token.trim_start().len());
+        let error = |kind| ParseFieldFilterError {
+            item: item.to_string(),
+            position: item_position,
+            exclude,
+            kind,
+        };
+
+        let matched: Vec<Field> = if item.is_empty() {
+            return Err(error(FieldFilterErrorKind::Empty));
+        } else if item.contains('*') {
+            let matched: Vec<Field> =
+                schema.iter().filter(|f| glob(item, f.title())).copied().collect();
+            if matched.is_empty() {
+                return Err(error(FieldFilterErrorKind::NoMatch));
+            }
+            matched
+        } else if let Some((first, last)) = item.split_once("..").or(item.split_once('-')) {
+            let position = |f: Field| match schema.iter().position(|s| *s == f) {
+                Some(i) => Ok(i),
+                None => Err(error(FieldFilterErrorKind::NotInDataset)),
+            };
+            let first = position(parse_field_item(schema, first.trim()).map_err(error)?)?;
+            let last = position(parse_field_item(schema, last.trim()).map_err(error)?)?;
+            if first > last {
+                return Err(error(FieldFilterErrorKind::InvalidRange));
+            }
+            schema[first..=last].to_vec()
+        } else {
+            vec![parse_field_item(schema, item).map_err(error)?]
+        };
+
+        for f in matched {
+            if !fields.contains(&f) {
+                fields.push(f);
+            }
+        }
+        position += token.len() + 1;
+    }
+
+    Ok(fields)
+}
+
+/// Parses a single title, or column index in `schema`.
+fn parse_field_item(schema: &[Field], item: &str) -> Result<Field, FieldFilterErrorKind> {
+    if !item.is_empty() && item.bytes().all(|b| b.is_ascii_digit()) {
+        match item.parse::<usize>() {
+            Ok(i) if (1..=schema.len()).contains(&i) => Ok(schema[i - 1]),
+            _ => Err(FieldFilterErrorKind::IndexOutOfRange),
+        }
+    } else {
+        Field::try_from(item).map_err(FieldFilterErrorKind::UnknownField)
+    }
+}
+
+/// Matches a title (or file name) against a pattern, where `*` matches any number of characters.
+pub(crate) fn glob(pattern: &str, title: &str) -> bool {
+    let mut parts = pattern.split('*');
+    // `split` always returns at least one part
+    let first = parts.next().unwrap();
+    let Some(mut rest) = title.strip_prefix(first) else {
+        return false;
+    };
+
+    let mut parts: Vec<&str> = parts.collect();
+    let last = match parts.pop() {
+        Some(last) => last,
+        None => return rest.is_empty(),
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+/// All the columns in the unmodified source CSVs downloaded from ODP (of any `Dataset`),
 /// these can be used as possible elements in a `FieldFilter`.
 #[repr(u8)]
 #[allow(non_camel_case_types)]
@@ -221,6 +399,12 @@ pub enum Field {
     Q_SurfaceTemp,
     WaterTemp,
     Q_WaterTemp,
+    RainSum,
+    Q_RainSum,
+    SnowDepth,
+    Q_SnowDepth,
+    Sunshine,
+    Q_Sunshine,
     EOR,
 }
 impl Field {
@@ -229,7 +413,8 @@ impl Field {
         FIELD_ARRAY[*self as usize].title
     }
 
-    fn _field_type(&self) -> &FieldType {
+    /// Category of the field.
+    pub(crate) fn field_type(&self) -> &FieldType {
         &FIELD_ARRAY[*self as usize].field_type
     }
 
@@ -256,3 +441,100 @@ impl TryFrom<&str> for Field {
         }
     }
 }
+#[cfg(feature = "serde")]
+impl serde::Serialize for Field {
+    /// Serializes the title.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.title())
+    }
+}
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Field {
+    /// Deserializes from the title.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let title = String::deserialize(deserializer)?;
+        Self::try_from(title.as_str()).map_err(serde::de::Error::custom)
+    }
+}
+
+/// The synoptic datasets published on ODP, differing in the time period of the records and in
+/// their columns.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Dataset {
+    /// 10-minute data, the only one with a "latest" file.
+    #[default]
+    TenMinute,
+    /// Hourly data.
+    Hourly,
+    /// Daily data, e.g. with minimum and maximum temperatures and precipitation sums.
+    Daily,
+}
+impl Dataset {
+    pub const ALL: [Dataset; 3] = [Dataset::TenMinute, Dataset::Hourly, Dataset::Daily];
+
+    /// All the columns of the dataset, in the order of the source CSV.
+    pub fn fields(&self) -> &'static [Field] {
+        match self {
+            Self::TenMinute => &TEN_MINUTE_FIELDS,
+            Self::Hourly => &HOURLY_FIELDS,
+            Self::Daily => &DAILY_FIELDS,
+        }
+    }
+
+    /// Name of the directory of the dataset on ODP.
+    pub fn dir(&self) -> &'static str {
+        match self {
+            Self::TenMinute => "10_minutes",
+            Self::Hourly => "hourly",
+            Self::Daily => "daily",
+        }
+    }
+
+    /// Common start of the names of the per-station archives, see `archive::ArchiveFile`.
+    pub fn archive_prefix(&self) -> &'static str {
+        match self {
+            Self::TenMinute => "HABP_10M",
+            Self::Hourly => "HABP_1H",
+            Self::Daily => "HABP_1D",
+        }
+    }
+
+    /// Common start of the file names in the dataset.
+    pub fn file_prefix(&self) -> &'static str {
+        match self {
+            Self::TenMinute => "HABP_10M_SYNOP",
+            Self::Hourly => "HABP_1H_SYNOP",
+            Self::Daily => "HABP_1D_SYNOP",
+        }
+    }
+
+    /// Finds the dataset of a field list (e.g. a header): the one with exactly these columns,
+    /// or else the first one containing all of them.
+    pub fn detect(fields: &[Field]) -> Option<Self> {
+        let contains_all = |d: &Dataset| fields.iter().all(|f| d.fields().contains(f));
+        Self::ALL
+            .into_iter()
+            .find(|d| d.fields() == fields)
+            .or_else(|| Self::ALL.into_iter().find(contains_all))
+    }
+}
+impl Display for Dataset {
+    /// Prints the directory name.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.dir())
+    }
+}
+impl TryFrom<&str> for Dataset {
+    type Error = ParseDatasetError;
+
+    /// Tries to convert from the directory name, or from the short form in the file names
+    /// (`10m`, `1h` or `1d`).
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        match s {
+            "10_minutes" | "10m" => Ok(Self::TenMinute),
+            "hourly" | "1h" => Ok(Self::Hourly),
+            "daily" | "1d" => Ok(Self::Daily),
+            _ => Err(ParseDatasetError(s.to_string())),
+        }
+    }
+}
@@ -9,6 +9,7 @@
 //! Helper items for the `field` module.
 
 use super::Field;
+use super::Field::*;
 
 /// Auxiliary characteristics of fields, used in `FIELD_ARRAY`.
 pub struct FieldProperties {
@@ -32,7 +33,7 @@ pub enum FieldType {
 ///
 /// There must be a 1:1 association to the `Field` enum, in matching order
 /// (i.e. array index == enum discriminant).
-pub const FIELD_ARRAY: [FieldProperties; 55] = [
+pub const FIELD_ARRAY: [FieldProperties; 61] = [
     FieldProperties {
         title: "Time",
         field: Field::Time,
@@ -357,6 +358,42 @@ pub const FIELD_ARRAY: [FieldProperties; 55] = [
         field_type: FieldType::Q,
         width: 6,
     },
+    FieldProperties {
+        title: "rs",
+        field: Field::RainSum,
+        field_type: FieldType::Value,
+        width: 5,
+    },
+    FieldProperties {
+        title: "Q_rs",
+        field: Field::Q_RainSum,
+        field_type: FieldType::Q,
+        width: 4,
+    },
+    FieldProperties {
+        title: "sd",
+        field: Field::SnowDepth,
+        field_type: FieldType::Value,
+        width: 4,
+    },
+    FieldProperties {
+        title: "Q_sd",
+        field: Field::Q_SnowDepth,
+        field_type: FieldType::Q,
+        width: 4,
+    },
+    FieldProperties {
+        title: "ss",
+        field: Field::Sunshine,
+        field_type: FieldType::Value,
+        width: 5,
+    },
+    FieldProperties {
+        title: "Q_ss",
+        field: Field::Q_Sunshine,
+        field_type: FieldType::Q,
+        width: 4,
+    },
     FieldProperties {
         title: "EOR",
         field: Field::EOR,
@@ -364,3 +401,39 @@ pub const FIELD_ARRAY: [FieldProperties; 55] = [
         width: 3,
     },
 ];
+
+/// Columns of the 10-minute data files, see `Dataset::fields`.
+pub const TEN_MINUTE_FIELDS: [Field; 55] = [
+    Time, StationNumber, StationName, Latitude, Longitude, Elevation,
+    Rain, Q_Rain, Temp, Q_Temp, TempAvg, Q_TempAvg, TempMin, Q_TempMin, TempMax, Q_TempMax,
+    Visibility, Q_Visibility, Pressure, Q_Pressure, Humidity, Q_Humidity,
+    GammaRad, Q_GammaRad, SolarRad, Q_SolarRad, UvRad, Q_UvRad,
+    WindSpeed, Q_WindSpeed, WindDir, Q_WindDir, GustSpeed, Q_GustSpeed, GustDir, Q_GustDir,
+    GustMinute, Q_GustMinute, GustSecond, Q_GustSecond,
+    GroundTemp5, Q_GroundTemp5, GroundTemp10, Q_GroundTemp10, GroundTemp20, Q_GroundTemp20,
+    GroundTemp50, Q_GroundTemp50, GroundTemp100, Q_GroundTemp100,
+    SurfaceTemp, Q_SurfaceTemp, WaterTemp, Q_WaterTemp,
+    EOR,
+];
+
+/// Columns of the hourly data files, see `Dataset::fields`.
+pub const HOURLY_FIELDS: [Field; 39] = [
+    Time, StationNumber, StationName, Latitude, Longitude, Elevation,
+    Rain, Q_Rain, Temp, Q_Temp, TempMin, Q_TempMin, TempMax, Q_TempMax,
+    Visibility, Q_Visibility, Pressure, Q_Pressure, Humidity, Q_Humidity,
+    SolarRad, Q_SolarRad, Sunshine, Q_Sunshine,
+    WindSpeed, Q_WindSpeed, WindDir, Q_WindDir, GustSpeed, Q_GustSpeed, GustDir, Q_GustDir,
+    GroundTemp5, Q_GroundTemp5, GroundTemp20, Q_GroundTemp20, SurfaceTemp, Q_SurfaceTemp,
+    EOR,
+];
+
+/// Columns of the daily data files, see `Dataset::fields`.
+pub const DAILY_FIELDS: [Field; 29] = [
+    Time, StationNumber, StationName, Latitude, Longitude, Elevation,
+    Temp, Q_Temp, TempMin, Q_TempMin, TempMax, Q_TempMax,
+    RainSum, Q_RainSum, SnowDepth, Q_SnowDepth, Sunshine, Q_Sunshine,
+    Pressure, Q_Pressure, Humidity, Q_Humidity,
+    WindSpeed, Q_WindSpeed, GustSpeed, Q_GustSpeed,
+    SurfaceTemp, Q_SurfaceTemp,
+    EOR,
+];
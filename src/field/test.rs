@@ -31,11 +31,12 @@ fn test_field_array() {
         .count();
     assert_eq!(count, 1);
 
-    // Width for all fields and delimiters equals length of the reference
+    // Width for all 10-minute fields and delimiters equals length of the reference
     let test_len = TEST_HEADER.len();
-    let calc_len = FIELD_ARRAY
-        .into_iter()
-        .map(|f| f.width)
+    let calc_len = Dataset::TenMinute
+        .fields()
+        .iter()
+        .map(|f| f.width())
         .fold(0_usize, |acc, w| acc + (w as usize) + 1);
     assert_eq!(calc_len, test_len + 1);
 
@@ -111,6 +112,10 @@ fn test_fieldconfig() {
         ("StationNumber;t;EOR", MissingTime),
         ("Time;StationName;t;EOR", MissingStationNumber),
         ("Time;StationNumber;EOR;t", InvalidEorPosition),
+        ("Time;StationNumber;ta;rs;EOR", UnknownDataset),
+        // The first problem in the order of the fields is reported
+        ("Time;StationNumber;t;t;Eor", FoundDuplicates("t".to_string())),
+        ("Time;StationNumber;Eor;t;t", UnknownField(ParseFieldError("Eor".to_string()))),
     ] {
         assert_eq!(
             FieldConfig::from_header(header, Delimiter::default()).unwrap_err(),
@@ -118,3 +123,161 @@ fn test_fieldconfig() {
         );
     }
 }
+
+// Checking the title patterns used in `FieldFilter::from_strs`.
+#[test]
+fn test_glob() {
+    for (pattern, title) in [
+        ("Q_*", "Q_t"),
+        ("*", "t"),
+        ("*t", "t"),
+        ("et*", "et100"),
+        ("*et*", "Q_et5"),
+        ("Q_*t*0", "Q_et100"),
+        ("Q_**", "Q_fx"),
+    ] {
+        assert!(glob(pattern, title), "\"{pattern}\" should match \"{title}\"");
+    }
+
+    for (pattern, title) in [
+        ("Q_*", "t"),
+        ("*t", "tx"),
+        ("et*", "Q_et5"),
+        ("Q_*t*0", "Q_et5"),
+        ("*x*x", "fx"),
+    ] {
+        assert!(!glob(pattern, title), "\"{pattern}\" should not match \"{title}\"");
+    }
+}
+
+// Checking `FieldFilter` parsing from strings.
+#[test]
+fn test_fieldfilter_from_strs() {
+    use Field::*;
+
+    let filter = FieldFilter::from_strs(Some("t, ta,6-8,fx..fxd"), Some("Q_*")).unwrap();
+    let expected = FieldFilter::new(
+        [Temp, TempAvg, Elevation, Rain, Q_Rain, GustSpeed, Q_GustSpeed, GustDir],
+        TEN_MINUTE_FIELDS.into_iter().filter(|f| f.title().starts_with("Q_")),
+    );
+    assert_eq!(filter, expected);
+
+    // Duplicates are removed, ranges can be mixed
+    let filter = FieldFilter::from_strs(Some("t,9,t..11,1"), None).unwrap();
+    assert_eq!(filter, FieldFilter::new([Temp, Q_Temp, TempAvg, Time], []));
+
+    assert_eq!(FieldFilter::from_strs(None, None), Ok(None));
+    assert_eq!(FieldFilter::from_strs(Some(""), Some(" ")), Ok(None));
+
+    for (include, exclude, item, position, kind) in [
+        (
+            "t,,ta",
+            "",
+            "",
+            2,
+            FieldFilterErrorKind::Empty,
+        ),
+        (
+            "t, T",
+            "",
+            "T",
+            3,
+            FieldFilterErrorKind::UnknownField(ParseFieldError("T".to_string())),
+        ),
+        (
+            "0",
+            "",
+            "0",
+            0,
+            FieldFilterErrorKind::IndexOutOfRange,
+        ),
+        (
+            "t,56",
+            "",
+            "56",
+            2,
+            FieldFilterErrorKind::IndexOutOfRange,
+        ),
+        (
+            "t",
+            "fx..t",
+            "fx..t",
+            0,
+            FieldFilterErrorKind::InvalidRange,
+        ),
+        (
+            "t",
+            "r, X_*",
+            "X_*",
+            3,
+            FieldFilterErrorKind::NoMatch,
+        ),
+        (
+            "t",
+            "rs..ss",
+            "rs..ss",
+            0,
+            FieldFilterErrorKind::NotInDataset,
+        ),
+        (
+            "t,6-x",
+            "",
+            "6-x",
+            2,
+            FieldFilterErrorKind::UnknownField(ParseFieldError("x".to_string())),
+        ),
+    ] {
+        let e = FieldFilter::from_strs(Some(include), Some(exclude)).unwrap_err();
+        assert_eq!(e.item(), item);
+        assert_eq!(e.position(), position);
+        assert_eq!(e.exclude(), !exclude.is_empty());
+        assert_eq!(e.kind(), &kind);
+    }
+}
+
+// Checking the column sets of the datasets, and finding them from a field list.
+#[test]
+fn test_dataset() {
+    use Field::*;
+
+    for dataset in Dataset::ALL {
+        let fields = dataset.fields();
+        assert_eq!(fields[..2], [Time, StationNumber], "{dataset}");
+        assert_eq!(fields.last(), Some(&EOR), "{dataset}");
+        // Every value is followed by its Q-field
+        for pair in fields[6..fields.len() - 1].chunks(2) {
+            assert_eq!(format!("Q_{}", pair[0]), pair[1].title(), "{dataset}");
+        }
+        assert_eq!(Dataset::detect(fields), Some(dataset));
+        assert_eq!(Dataset::try_from(dataset.to_string().as_str()), Ok(dataset));
+    }
+    assert_eq!(TEN_MINUTE_FIELDS.len() + 6, FIELD_ARRAY.len());
+
+    // Subsets belong to the first dataset containing them
+    assert_eq!(Dataset::detect(&[Time, StationNumber, Temp]), Some(Dataset::TenMinute));
+    assert_eq!(Dataset::detect(&[Time, StationNumber, Sunshine]), Some(Dataset::Hourly));
+    assert_eq!(Dataset::detect(&[Time, StationNumber, RainSum]), Some(Dataset::Daily));
+    assert_eq!(Dataset::detect(&[Time, TempAvg, RainSum]), None);
+    assert_eq!(Dataset::try_from("1d"), Ok(Dataset::Daily));
+    assert!(Dataset::try_from("monthly").is_err());
+
+    // Selecting and filtering the daily columns
+    let header = "Time;StationNumber;tn;tx;rs;sd";
+    let ffilter = FieldFilter::from_strs_for(Dataset::Daily, Some("9-12,rs..sd"), None).unwrap();
+    let expected = [TempMin, Q_TempMin, TempMax, Q_TempMax, RainSum, Q_RainSum, SnowDepth];
+    assert_eq!(ffilter, FieldFilter::new(expected, []));
+    // Fields of other datasets are ignored
+    let ffilter = FieldFilter::new([TempMin, TempMax, RainSum, SnowDepth, TempAvg], []);
+    let expected =
+        FieldConfig::new_for(Dataset::Daily, false, false, false, false, ffilter.as_ref());
+    let config = FieldConfig::from_header(header, Delimiter::default()).unwrap();
+    assert_eq!(config, expected);
+    assert_eq!(config.dataset(), Dataset::Daily);
+
+    let config = FieldConfig::new_for(Dataset::Daily, true, true, true, true, None);
+    assert_eq!(config.fields().copied().collect::<Vec<Field>>(), DAILY_FIELDS);
+    assert_eq!(FieldConfig::new_with_all().dataset(), Dataset::TenMinute);
+
+    let ffilter = FieldFilter::from_strs_for(Dataset::Hourly, None, Some("Q_*")).unwrap();
+    assert_eq!(ffilter.unwrap().excluding.len(), (HOURLY_FIELDS.len() - 7) / 2);
+}
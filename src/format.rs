@@ -16,12 +16,18 @@ use std::fmt::Display;
 use std::str::FromStr;
 
 #[cfg(test)]
+#[allow(clippy::redundant_pattern_matching)]
 mod test;
 
 /// How to represent a missing value in CSV.
+///
+/// With the `serde` feature enabled, it is (de)serialized as its string representation.
 #[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub enum MissingValue {
     /// "-999", this is the default.
+    ///
+    /// Only used for numeric fields, the others (i.e. `StationName` and the Q-fields) will be
+    /// left empty, as it is done in the ODP files.
     Minus999,
     /// "null"
     Null,
@@ -57,8 +63,23 @@ impl TryFrom<&str> for MissingValue {
         }
     }
 }
+#[cfg(feature = "serde")]
+impl serde::Serialize for MissingValue {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for MissingValue {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Self::try_from(s.as_str()).map_err(serde::de::Error::custom)
+    }
+}
 
 /// Field separator character.
+///
+/// With the `serde` feature enabled, it is (de)serialized as a `char`.
 #[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct Delimiter(char);
 impl AsRef<char> for Delimiter {
@@ -90,12 +111,27 @@ impl TryFrom<char> for Delimiter {
         }
     }
 }
+#[cfg(feature = "serde")]
+impl serde::Serialize for Delimiter {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_char(self.0)
+    }
+}
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Delimiter {
+    /// Fails the same way as `try_from`.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let ch = char::deserialize(deserializer)?;
+        Self::try_from(ch).map_err(serde::de::Error::custom)
+    }
+}
 
 /// CSV formatting properties including the selected fields.
 ///
 /// It is used mainly to define the output CSV, but also helps in checking that every input line
 /// conforms to the same format.
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CsvFormat {
     /// - `true`: The field will be padded with spaces to the default width and the value aligned
     ///   within to the right (except for `StationName` which is left-aligned).
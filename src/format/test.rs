@@ -110,3 +110,32 @@ fn test_csv_format() {
         assert_eq!(CsvFormat::from_str(header).unwrap_err(), eexpected);
     }
 }
+
+// Checking that the format is (de)serialized field by field, with the same checks.
+#[cfg(feature = "serde")]
+#[test]
+fn test_csvformat_serde() {
+    let format = CsvFormat {
+        alignment: false,
+        missing: MissingValue::Null,
+        delimiter: Delimiter::try_from('\t').unwrap(),
+        field_config: FieldConfig::from_header("Time,StationNumber,t,fx,EOR", Delimiter(','))
+            .unwrap(),
+    };
+    let json = serde_json::to_string(&format).unwrap();
+    assert_eq!(
+        json,
+        r#"{"alignment":false,"missing":"null","delimiter":"\t","field_config":["Time","StationNumber","t","fx","EOR"]}"#,
+    );
+    assert_eq!(serde_json::from_str::<CsvFormat>(&json).unwrap(), format);
+
+    // Invalid values are rejected the same way as in the constructors
+    for json in [
+        r#"{"alignment":true,"missing":"-1","delimiter":";","field_config":["Time","StationNumber"]}"#,
+        r#"{"alignment":true,"missing":"","delimiter":"x","field_config":["Time","StationNumber"]}"#,
+        r#"{"alignment":true,"missing":"","delimiter":";","field_config":["StationNumber","Time"]}"#,
+        r#"{"alignment":true,"missing":"","delimiter":";","field_config":["Time","StationNumber","T"]}"#,
+    ] {
+        assert!(serde_json::from_str::<CsvFormat>(json).is_err());
+    }
+}
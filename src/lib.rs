@@ -10,11 +10,17 @@ pub mod error;
 pub mod field;
 pub mod format;
 pub mod record;
+pub mod timestamp;
+pub mod validation;
 
+pub mod archive;
+pub mod config;
+pub mod copy;
 pub mod csv;
-//~ pub mod download;
-//~ pub mod zip;
-
-pub fn hello() {
-    println!("Hello World!");
-}
+pub mod daemon;
+#[cfg(feature = "sqlite")]
+pub mod db;
+pub mod download;
+pub mod sink;
+pub mod sql;
+pub mod zip;
@@ -9,9 +9,17 @@
 //! Types for storing measurement data records.
 
 use crate::error::*;
-use crate::format::CsvFormat;
+use crate::field::field_properties::FieldType;
+use crate::field::Field;
+use crate::format::{CsvFormat, MissingValue};
+use crate::timestamp::Timestamp;
 
 use std::hash::{Hash, Hasher};
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+#[cfg(test)]
+pub(crate) mod test;
 
 /// Stores a line of values as individual strings.
 ///
@@ -21,7 +29,8 @@ use std::hash::{Hash, Hasher};
 ///
 /// Fields `time` and `station_number` are mandatory, but all the others are wrapped in `Option`
 /// to be able to represent missing values and filtered columns.
-#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Debug, Default, Eq, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RawRecord {
     time: String,
     station_number: String,
@@ -77,19 +86,289 @@ pub struct RawRecord {
     q_surface_temp: Option<String>,
     water_temp: Option<String>,
     q_water_temp: Option<String>,
+    rain_sum: Option<String>,
+    q_rain_sum: Option<String>,
+    snow_depth: Option<String>,
+    q_snow_depth: Option<String>,
+    sunshine: Option<String>,
+    q_sunshine: Option<String>,
     eor: Option<String>,
 }
 impl RawRecord {
     /// Tries to parse a CSV line using the specified format.
-    pub fn from_csv(_line: &str, _format: CsvFormat) -> Result<Self, ParseRecordError> {
-        todo!()
+    ///
+    /// Values are trimmed, so padding is accepted regardless of `alignment`, except when the
+    /// delimiter is a whitespace character: in that case an aligned line is sliced by the field
+    /// widths instead of being split at every delimiter.
+    ///
+    /// All of the `MissingValue` representations are mapped to `None`. A trailing line
+    /// terminator is ignored.
+    pub fn from_csv(line: &str, format: &CsvFormat) -> Result<Self, ParseRecordError> {
+        let line = line.trim_end_matches(['\r', '\n']);
+        let delim = *format.delimiter.as_ref();
+        let fields: Vec<Field> = format.field_config.fields().copied().collect();
+
+        let values: Vec<&str> = if format.alignment && delim.is_ascii_whitespace() {
+            split_aligned(line, delim, &fields)?
+        } else {
+            line.split(delim).collect()
+        };
+        // Character position of each value, for the error context
+        let columns: Vec<usize> = values
+            .iter()
+            .scan(1, |column, v| {
+                let start = *column;
+                *column += v.chars().count() + 1;
+                Some(start)
+            })
+            .collect();
+        if values.len() != fields.len() {
+            return Err(ParseRecordError::FieldCount {
+                expected: fields.len(),
+                found: values.len(),
+                column: columns
+                    .get(fields.len())
+                    .copied()
+                    .unwrap_or(line.chars().count() + 1),
+            });
+        }
+
+        let mut record = Self::default();
+        for ((field, value), column) in fields.into_iter().zip(values).zip(columns) {
+            let value = value.trim();
+            match MissingValue::try_from(value) {
+                Ok(_) => {
+                    if matches!(field, Field::Time | Field::StationNumber) {
+                        return Err(ParseRecordError::MissingMandatory { field, column });
+                    }
+                }
+                Err(_) => record.set(field, Some(value.to_string())),
+            }
+        }
+
+        Ok(record)
     }
 
     /// Creates a CSV line by concatenating the fields according to the specified format.
-    pub fn to_csv(&self, _format: CsvFormat) -> String {
-        todo!()
+    ///
+    /// Only the fields in the `FieldConfig` of the format are included, missing values are
+    /// written as specified by `missing`. The line terminator is not appended.
+    pub fn to_csv(&self, format: &CsvFormat) -> String {
+        let mut line = String::new();
+        for (i, f) in format.field_config.fields().enumerate() {
+            if i != 0 {
+                line.push(*format.delimiter.as_ref());
+            }
+
+            let missing;
+            let value = match self.get(*f) {
+                Some(v) => v,
+                None => {
+                    missing = missing_str(*f, format.missing);
+                    &missing
+                }
+            };
+
+            let width = usize::from(f.width());
+            match (format.alignment, *f == Field::StationName) {
+                (true, true) => line.push_str(&format!("{:<1$}", value, width)),
+                (true, false) => line.push_str(&format!("{:>1$}", value, width)),
+                (false, _) => line.push_str(value),
+            }
+        }
+
+        line
+    }
+
+    /// Returns the value of a field, or `None` if it is missing.
+    pub fn get(&self, field: Field) -> Option<&str> {
+        match field {
+            Field::Time => Some(&self.time),
+            Field::StationNumber => Some(&self.station_number),
+            Field::StationName => self.station_name.as_deref(),
+            Field::Latitude => self.latitude.as_deref(),
+            Field::Longitude => self.longitude.as_deref(),
+            Field::Elevation => self.elevation.as_deref(),
+            Field::Rain => self.rain.as_deref(),
+            Field::Q_Rain => self.q_rain.as_deref(),
+            Field::Temp => self.temp.as_deref(),
+            Field::Q_Temp => self.q_temp.as_deref(),
+            Field::TempAvg => self.temp_avg.as_deref(),
+            Field::Q_TempAvg => self.q_temp_avg.as_deref(),
+            Field::TempMin => self.temp_min.as_deref(),
+            Field::Q_TempMin => self.q_temp_min.as_deref(),
+            Field::TempMax => self.temp_max.as_deref(),
+            Field::Q_TempMax => self.q_temp_max.as_deref(),
+            Field::Visibility => self.visibility.as_deref(),
+            Field::Q_Visibility => self.q_visibility.as_deref(),
+            Field::Pressure => self.pressure.as_deref(),
+            Field::Q_Pressure => self.q_pressure.as_deref(),
+            Field::Humidity => self.humidity.as_deref(),
+            Field::Q_Humidity => self.q_humidity.as_deref(),
+            Field::GammaRad => self.gamma_rad.as_deref(),
+            Field::Q_GammaRad => self.q_gamma_rad.as_deref(),
+            Field::SolarRad => self.solar_rad.as_deref(),
+            Field::Q_SolarRad => self.q_solar_rad.as_deref(),
+            Field::UvRad => self.uv_rad.as_deref(),
+            Field::Q_UvRad => self.q_uv_rad.as_deref(),
+            Field::WindSpeed => self.wind_speed.as_deref(),
+            Field::Q_WindSpeed => self.q_wind_speed.as_deref(),
+            Field::WindDir => self.wind_dir.as_deref(),
+            Field::Q_WindDir => self.q_wind_dir.as_deref(),
+            Field::GustSpeed => self.gust_speed.as_deref(),
+            Field::Q_GustSpeed => self.q_gust_speed.as_deref(),
+            Field::GustDir => self.gust_dir.as_deref(),
+            Field::Q_GustDir => self.q_gust_dir.as_deref(),
+            Field::GustMinute => self.gust_minute.as_deref(),
+            Field::Q_GustMinute => self.q_gust_minute.as_deref(),
+            Field::GustSecond => self.gust_second.as_deref(),
+            Field::Q_GustSecond => self.q_gust_second.as_deref(),
+            Field::GroundTemp5 => self.ground_temp_5.as_deref(),
+            Field::Q_GroundTemp5 => self.q_ground_temp_5.as_deref(),
+            Field::GroundTemp10 => self.ground_temp_10.as_deref(),
+            Field::Q_GroundTemp10 => self.q_ground_temp_10.as_deref(),
+            Field::GroundTemp20 => self.ground_temp_20.as_deref(),
+            Field::Q_GroundTemp20 => self.q_ground_temp_20.as_deref(),
+            Field::GroundTemp50 => self.ground_temp_50.as_deref(),
+            Field::Q_GroundTemp50 => self.q_ground_temp_50.as_deref(),
+            Field::GroundTemp100 => self.ground_temp_100.as_deref(),
+            Field::Q_GroundTemp100 => self.q_ground_temp_100.as_deref(),
+            Field::SurfaceTemp => self.surface_temp.as_deref(),
+            Field::Q_SurfaceTemp => self.q_surface_temp.as_deref(),
+            Field::WaterTemp => self.water_temp.as_deref(),
+            Field::Q_WaterTemp => self.q_water_temp.as_deref(),
+            Field::RainSum => self.rain_sum.as_deref(),
+            Field::Q_RainSum => self.q_rain_sum.as_deref(),
+            Field::SnowDepth => self.snow_depth.as_deref(),
+            Field::Q_SnowDepth => self.q_snow_depth.as_deref(),
+            Field::Sunshine => self.sunshine.as_deref(),
+            Field::Q_Sunshine => self.q_sunshine.as_deref(),
+            Field::EOR => self.eor.as_deref(),
+        }
+    }
+
+    /// Sets the value of a field. For the mandatory fields `None` results in an empty string.
+    pub(crate) fn set(&mut self, field: Field, value: Option<String>) {
+        match field {
+            Field::Time => self.time = value.unwrap_or_default(),
+            Field::StationNumber => self.station_number = value.unwrap_or_default(),
+            Field::StationName => self.station_name = value,
+            Field::Latitude => self.latitude = value,
+            Field::Longitude => self.longitude = value,
+            Field::Elevation => self.elevation = value,
+            Field::Rain => self.rain = value,
+            Field::Q_Rain => self.q_rain = value,
+            Field::Temp => self.temp = value,
+            Field::Q_Temp => self.q_temp = value,
+            Field::TempAvg => self.temp_avg = value,
+            Field::Q_TempAvg => self.q_temp_avg = value,
+            Field::TempMin => self.temp_min = value,
+            Field::Q_TempMin => self.q_temp_min = value,
+            Field::TempMax => self.temp_max = value,
+            Field::Q_TempMax => self.q_temp_max = value,
+            Field::Visibility => self.visibility = value,
+            Field::Q_Visibility => self.q_visibility = value,
+            Field::Pressure => self.pressure = value,
+            Field::Q_Pressure => self.q_pressure = value,
+            Field::Humidity => self.humidity = value,
+            Field::Q_Humidity => self.q_humidity = value,
+            Field::GammaRad => self.gamma_rad = value,
+            Field::Q_GammaRad => self.q_gamma_rad = value,
+            Field::SolarRad => self.solar_rad = value,
+            Field::Q_SolarRad => self.q_solar_rad = value,
+            Field::UvRad => self.uv_rad = value,
+            Field::Q_UvRad => self.q_uv_rad = value,
+            Field::WindSpeed => self.wind_speed = value,
+            Field::Q_WindSpeed => self.q_wind_speed = value,
+            Field::WindDir => self.wind_dir = value,
+            Field::Q_WindDir => self.q_wind_dir = value,
+            Field::GustSpeed => self.gust_speed = value,
+            Field::Q_GustSpeed => self.q_gust_speed = value,
+            Field::GustDir => self.gust_dir = value,
+            Field::Q_GustDir => self.q_gust_dir = value,
+            Field::GustMinute => self.gust_minute = value,
+            Field::Q_GustMinute => self.q_gust_minute = value,
+            Field::GustSecond => self.gust_second = value,
+            Field::Q_GustSecond => self.q_gust_second = value,
+            Field::GroundTemp5 => self.ground_temp_5 = value,
+            Field::Q_GroundTemp5 => self.q_ground_temp_5 = value,
+            Field::GroundTemp10 => self.ground_temp_10 = value,
+            Field::Q_GroundTemp10 => self.q_ground_temp_10 = value,
+            Field::GroundTemp20 => self.ground_temp_20 = value,
+            Field::Q_GroundTemp20 => self.q_ground_temp_20 = value,
+            Field::GroundTemp50 => self.ground_temp_50 = value,
+            Field::Q_GroundTemp50 => self.q_ground_temp_50 = value,
+            Field::GroundTemp100 => self.ground_temp_100 = value,
+            Field::Q_GroundTemp100 => self.q_ground_temp_100 = value,
+            Field::SurfaceTemp => self.surface_temp = value,
+            Field::Q_SurfaceTemp => self.q_surface_temp = value,
+            Field::WaterTemp => self.water_temp = value,
+            Field::Q_WaterTemp => self.q_water_temp = value,
+            Field::RainSum => self.rain_sum = value,
+            Field::Q_RainSum => self.q_rain_sum = value,
+            Field::SnowDepth => self.snow_depth = value,
+            Field::Q_SnowDepth => self.q_snow_depth = value,
+            Field::Sunshine => self.sunshine = value,
+            Field::Q_Sunshine => self.q_sunshine = value,
+            Field::EOR => self.eor = value,
+        }
+    }
+}
+
+/// Slices an aligned line by the field widths, checking the delimiters in between.
+///
+/// The last field takes the rest of the line, so that a missing padding at the end does not
+/// count as an error.
+fn split_aligned<'a>(
+    line: &'a str,
+    delim: char,
+    fields: &[Field],
+) -> Result<Vec<&'a str>, ParseRecordError> {
+    let mut values = Vec::with_capacity(fields.len());
+    let mut rest = line;
+    let mut column = 1;
+    for (i, f) in fields.iter().enumerate() {
+        if i == fields.len() - 1 {
+            values.push(rest);
+            break;
+        }
+
+        let end = rest
+            .char_indices()
+            .nth(usize::from(f.width()))
+            .map(|(pos, _)| pos)
+            .ok_or(ParseRecordError::FieldCount {
+                expected: fields.len(),
+                found: i + 1,
+                column: line.chars().count() + 1,
+            })?;
+        if !rest[end..].starts_with(delim) {
+            return Err(ParseRecordError::Misaligned {
+                field: *f,
+                column: column + usize::from(f.width()),
+            });
+        }
+        values.push(&rest[..end]);
+        column += usize::from(f.width()) + 1;
+        rest = &rest[end + delim.len_utf8()..];
     }
+
+    Ok(values)
 }
+
+/// String to write for a missing value.
+///
+/// `EOR` is a constant marker, so it is always written out. The `-999` pattern is only used for
+/// numeric fields, the others are left empty (as it is done in the ODP files).
+fn missing_str(field: Field, missing: MissingValue) -> String {
+    match (field.field_type(), missing) {
+        (FieldType::EOR, _) => Field::EOR.title().to_string(),
+        (FieldType::Q, MissingValue::Minus999) => String::new(),
+        (_, MissingValue::Minus999) if field == Field::StationName => String::new(),
+        (_, m) => m.to_string(),
+    }
+}
+
 impl Hash for RawRecord {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.time.hash(state);
@@ -97,10 +376,24 @@ impl Hash for RawRecord {
     }
 }
 
+/// A single value of a `MetRecord`, in its natural data type.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum MetValue<'a> {
+    /// `Time`
+    Timestamp(Timestamp),
+    /// `StationNumber`
+    Integer(usize),
+    /// Measurement values and station coordinates.
+    Float(f64),
+    /// `StationName`, the Q-fields and `EOR`.
+    Text(&'a str),
+}
+
 /// Stores field values in their natural data types.
 #[derive(Clone, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MetRecord {
-    time: u64, //TODO chrono::DateTime
+    time: Timestamp,
     station_number: usize,
     station_name: Option<String>,
     latitude: Option<f64>,
@@ -154,8 +447,93 @@ pub struct MetRecord {
     q_surface_temp: Option<String>,
     water_temp: Option<f64>,
     q_water_temp: Option<String>,
+    rain_sum: Option<f64>,
+    q_rain_sum: Option<String>,
+    snow_depth: Option<f64>,
+    q_snow_depth: Option<String>,
+    sunshine: Option<f64>,
+    q_sunshine: Option<String>,
     eor: Option<String>,
 }
+impl MetRecord {
+    /// Returns the value of `Time`, which is never missing.
+    pub fn time(&self) -> Timestamp {
+        self.time
+    }
+
+    /// Returns the value of `Time` as a chrono type.
+    #[cfg(feature = "chrono")]
+    pub fn datetime(&self) -> chrono::DateTime<chrono::Utc> {
+        self.time.to_datetime()
+    }
+
+    /// Returns the value of a field, or `None` if it is missing.
+    pub fn get(&self, field: Field) -> Option<MetValue<'_>> {
+        match field {
+            Field::Time => Some(MetValue::Timestamp(self.time)),
+            Field::StationNumber => Some(MetValue::Integer(self.station_number)),
+            Field::StationName => self.station_name.as_deref().map(MetValue::Text),
+            Field::Latitude => self.latitude.map(MetValue::Float),
+            Field::Longitude => self.longitude.map(MetValue::Float),
+            Field::Elevation => self.elevation.map(MetValue::Float),
+            Field::Rain => self.rain.map(MetValue::Float),
+            Field::Q_Rain => self.q_rain.as_deref().map(MetValue::Text),
+            Field::Temp => self.temp.map(MetValue::Float),
+            Field::Q_Temp => self.q_temp.as_deref().map(MetValue::Text),
+            Field::TempAvg => self.temp_avg.map(MetValue::Float),
+            Field::Q_TempAvg => self.q_temp_avg.as_deref().map(MetValue::Text),
+            Field::TempMin => self.temp_min.map(MetValue::Float),
+            Field::Q_TempMin => self.q_temp_min.as_deref().map(MetValue::Text),
+            Field::TempMax => self.temp_max.map(MetValue::Float),
+            Field::Q_TempMax => self.q_temp_max.as_deref().map(MetValue::Text),
+            Field::Visibility => self.visibility.map(MetValue::Float),
+            Field::Q_Visibility => self.q_visibility.as_deref().map(MetValue::Text),
+            Field::Pressure => self.pressure.map(MetValue::Float),
+            Field::Q_Pressure => self.q_pressure.as_deref().map(MetValue::Text),
+            Field::Humidity => self.humidity.map(MetValue::Float),
+            Field::Q_Humidity => self.q_humidity.as_deref().map(MetValue::Text),
+            Field::GammaRad => self.gamma_rad.map(MetValue::Float),
+            Field::Q_GammaRad => self.q_gamma_rad.as_deref().map(MetValue::Text),
+            Field::SolarRad => self.solar_rad.map(MetValue::Float),
+            Field::Q_SolarRad => self.q_solar_rad.as_deref().map(MetValue::Text),
+            Field::UvRad => self.uv_rad.map(MetValue::Float),
+            Field::Q_UvRad => self.q_uv_rad.as_deref().map(MetValue::Text),
+            Field::WindSpeed => self.wind_speed.map(MetValue::Float),
+            Field::Q_WindSpeed => self.q_wind_speed.as_deref().map(MetValue::Text),
+            Field::WindDir => self.wind_dir.map(MetValue::Float),
+            Field::Q_WindDir => self.q_wind_dir.as_deref().map(MetValue::Text),
+            Field::GustSpeed => self.gust_speed.map(MetValue::Float),
+            Field::Q_GustSpeed => self.q_gust_speed.as_deref().map(MetValue::Text),
+            Field::GustDir => self.gust_dir.map(MetValue::Float),
+            Field::Q_GustDir => self.q_gust_dir.as_deref().map(MetValue::Text),
+            Field::GustMinute => self.gust_minute.map(MetValue::Float),
+            Field::Q_GustMinute => self.q_gust_minute.as_deref().map(MetValue::Text),
+            Field::GustSecond => self.gust_second.map(MetValue::Float),
+            Field::Q_GustSecond => self.q_gust_second.as_deref().map(MetValue::Text),
+            Field::GroundTemp5 => self.ground_temp_5.map(MetValue::Float),
+            Field::Q_GroundTemp5 => self.q_ground_temp_5.as_deref().map(MetValue::Text),
+            Field::GroundTemp10 => self.ground_temp_10.map(MetValue::Float),
+            Field::Q_GroundTemp10 => self.q_ground_temp_10.as_deref().map(MetValue::Text),
+            Field::GroundTemp20 => self.ground_temp_20.map(MetValue::Float),
+            Field::Q_GroundTemp20 => self.q_ground_temp_20.as_deref().map(MetValue::Text),
+            Field::GroundTemp50 => self.ground_temp_50.map(MetValue::Float),
+            Field::Q_GroundTemp50 => self.q_ground_temp_50.as_deref().map(MetValue::Text),
+            Field::GroundTemp100 => self.ground_temp_100.map(MetValue::Float),
+            Field::Q_GroundTemp100 => self.q_ground_temp_100.as_deref().map(MetValue::Text),
+            Field::SurfaceTemp => self.surface_temp.map(MetValue::Float),
+            Field::Q_SurfaceTemp => self.q_surface_temp.as_deref().map(MetValue::Text),
+            Field::WaterTemp => self.water_temp.map(MetValue::Float),
+            Field::Q_WaterTemp => self.q_water_temp.as_deref().map(MetValue::Text),
+            Field::RainSum => self.rain_sum.map(MetValue::Float),
+            Field::Q_RainSum => self.q_rain_sum.as_deref().map(MetValue::Text),
+            Field::SnowDepth => self.snow_depth.map(MetValue::Float),
+            Field::Q_SnowDepth => self.q_snow_depth.as_deref().map(MetValue::Text),
+            Field::Sunshine => self.sunshine.map(MetValue::Float),
+            Field::Q_Sunshine => self.q_sunshine.as_deref().map(MetValue::Text),
+            Field::EOR => self.eor.as_deref().map(MetValue::Text),
+        }
+    }
+}
 impl Hash for MetRecord {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.time.hash(state);
@@ -166,16 +544,130 @@ impl TryFrom<RawRecord> for MetRecord {
     type Error = TryFromRawRecordError;
 
     /// String fields are moved, numeric fields are parsed.
-    fn try_from(_value: RawRecord) -> Result<Self, Self::Error> {
-        todo!()
+    ///
+    /// Any of the `MissingValue` representations in a numeric field is mapped to `None`.
+    fn try_from(value: RawRecord) -> Result<Self, Self::Error> {
+        let parser = ValueParser {
+            station_number: &value.station_number,
+        };
+
+        Ok(MetRecord {
+            time: parser.timestamp(&value.time)?,
+            station_number: parser.integer(Field::StationNumber, &value.station_number)?,
+            station_name: value.station_name,
+            latitude: parser.float(Field::Latitude, value.latitude)?,
+            longitude: parser.float(Field::Longitude, value.longitude)?,
+            elevation: parser.float(Field::Elevation, value.elevation)?,
+            rain: parser.float(Field::Rain, value.rain)?,
+            q_rain: value.q_rain,
+            temp: parser.float(Field::Temp, value.temp)?,
+            q_temp: value.q_temp,
+            temp_avg: parser.float(Field::TempAvg, value.temp_avg)?,
+            q_temp_avg: value.q_temp_avg,
+            temp_min: parser.float(Field::TempMin, value.temp_min)?,
+            q_temp_min: value.q_temp_min,
+            temp_max: parser.float(Field::TempMax, value.temp_max)?,
+            q_temp_max: value.q_temp_max,
+            visibility: parser.float(Field::Visibility, value.visibility)?,
+            q_visibility: value.q_visibility,
+            pressure: parser.float(Field::Pressure, value.pressure)?,
+            q_pressure: value.q_pressure,
+            humidity: parser.float(Field::Humidity, value.humidity)?,
+            q_humidity: value.q_humidity,
+            gamma_rad: parser.float(Field::GammaRad, value.gamma_rad)?,
+            q_gamma_rad: value.q_gamma_rad,
+            solar_rad: parser.float(Field::SolarRad, value.solar_rad)?,
+            q_solar_rad: value.q_solar_rad,
+            uv_rad: parser.float(Field::UvRad, value.uv_rad)?,
+            q_uv_rad: value.q_uv_rad,
+            wind_speed: parser.float(Field::WindSpeed, value.wind_speed)?,
+            q_wind_speed: value.q_wind_speed,
+            wind_dir: parser.float(Field::WindDir, value.wind_dir)?,
+            q_wind_dir: value.q_wind_dir,
+            gust_speed: parser.float(Field::GustSpeed, value.gust_speed)?,
+            q_gust_speed: value.q_gust_speed,
+            gust_dir: parser.float(Field::GustDir, value.gust_dir)?,
+            q_gust_dir: value.q_gust_dir,
+            gust_minute: parser.float(Field::GustMinute, value.gust_minute)?,
+            q_gust_minute: value.q_gust_minute,
+            gust_second: parser.float(Field::GustSecond, value.gust_second)?,
+            q_gust_second: value.q_gust_second,
+            ground_temp_5: parser.float(Field::GroundTemp5, value.ground_temp_5)?,
+            q_ground_temp_5: value.q_ground_temp_5,
+            ground_temp_10: parser.float(Field::GroundTemp10, value.ground_temp_10)?,
+            q_ground_temp_10: value.q_ground_temp_10,
+            ground_temp_20: parser.float(Field::GroundTemp20, value.ground_temp_20)?,
+            q_ground_temp_20: value.q_ground_temp_20,
+            ground_temp_50: parser.float(Field::GroundTemp50, value.ground_temp_50)?,
+            q_ground_temp_50: value.q_ground_temp_50,
+            ground_temp_100: parser.float(Field::GroundTemp100, value.ground_temp_100)?,
+            q_ground_temp_100: value.q_ground_temp_100,
+            surface_temp: parser.float(Field::SurfaceTemp, value.surface_temp)?,
+            q_surface_temp: value.q_surface_temp,
+            water_temp: parser.float(Field::WaterTemp, value.water_temp)?,
+            q_water_temp: value.q_water_temp,
+            rain_sum: parser.float(Field::RainSum, value.rain_sum)?,
+            q_rain_sum: value.q_rain_sum,
+            snow_depth: parser.float(Field::SnowDepth, value.snow_depth)?,
+            q_snow_depth: value.q_snow_depth,
+            sunshine: parser.float(Field::Sunshine, value.sunshine)?,
+            q_sunshine: value.q_sunshine,
+            eor: value.eor,
+        })
     }
 }
 impl TryFrom<&RawRecord> for MetRecord {
     type Error = TryFromRawRecordError;
 
     /// String fields are copied, numeric fields are parsed.
-    fn try_from(_value: &RawRecord) -> Result<Self, Self::Error> {
-        todo!()
+    fn try_from(value: &RawRecord) -> Result<Self, Self::Error> {
+        Self::try_from(value.clone())
+    }
+}
+
+/// Helper for the `MetRecord` conversion, to add context to parsing errors.
+struct ValueParser<'a> {
+    station_number: &'a str,
+}
+impl ValueParser<'_> {
+    fn integer<T>(&self, field: Field, value: &str) -> Result<T, TryFromRawRecordError>
+    where
+        T: FromStr<Err = ParseIntError>,
+    {
+        value
+            .parse()
+            .map_err(|e| self.error(field, value, ParseValueError::Integer(e)))
+    }
+
+    fn timestamp(&self, value: &str) -> Result<Timestamp, TryFromRawRecordError> {
+        value
+            .parse()
+            .map_err(|e| self.error(Field::Time, value, ParseValueError::Timestamp(e)))
+    }
+
+    fn float(
+        &self,
+        field: Field,
+        value: Option<String>,
+    ) -> Result<Option<f64>, TryFromRawRecordError> {
+        match value {
+            Some(v) if MissingValue::try_from(v.as_str()).is_err() => match v.parse::<f64>() {
+                Ok(x) if x.is_finite() => Ok(Some(x)),
+                Ok(_) => Err(self.error(field, &v, ParseValueError::NonFinite)),
+                Err(e) => Err(self.error(field, &v, ParseValueError::Float(e))),
+            },
+            _ => Ok(None),
+        }
+    }
+
+    fn error(&self, field: Field, value: &str, reason: ParseValueError) -> TryFromRawRecordError {
+        TryFromRawRecordError {
+            line: None,
+            station_number: self.station_number.to_string(),
+            field,
+            value: value.to_string(),
+            reason,
+        }
     }
 }
 
@@ -191,15 +683,108 @@ pub struct RecordFilter<'a> {
 impl<'a> RecordFilter<'a> {
     /// Tries to parse a comma-separated list of station numbers and names, supplied as a string
     /// (e.g. by the user).
+    ///
+    /// Items consisting of digits only are taken as station numbers, anything else as station
+    /// names. Names may be patterns with the syntax of SQL `LIKE`, i.e. `%` matches any number of
+    /// characters and `_` matches exactly one. Name matching is case- and accent-insensitive, so
+    /// for example "pestszentlorinc" matches "Pestszentlőrinc".
+    ///
+    /// With `invert` set, the filter will keep the records that do *not* match the list.
+    ///
+    /// Returns `None` if the list is empty, meaning that no filtering is needed.
     pub fn from_str(
-        _filter_str: &'a str,
-        _invert: bool,
+        filter_str: &'a str,
+        invert: bool,
     ) -> Result<Option<Self>, ParseRecordFilterError> {
-        todo!()
+        if filter_str.trim().is_empty() {
+            return Ok(None);
+        }
+
+        let mut name_set = Vec::new();
+        let mut number_set = Vec::new();
+        let mut position = 0;
+        for token in filter_str.split(',') {
+            let item = token.trim();
+            if item.is_empty() {
+                return Err(ParseRecordFilterError::new(position));
+            }
+            if item.bytes().all(|b| b.is_ascii_digit()) {
+                number_set.push(item);
+            } else {
+                name_set.push(item);
+            }
+            position += token.len() + 1;
+        }
+
+        Ok(Some(Self {
+            name_set,
+            number_set,
+            exclude: invert,
+        }))
     }
 
     /// Function to use with `Iterator.filter`.
-    pub fn filter(_rec: &RawRecord) -> bool {
-        todo!()
+    pub fn filter(&self, rec: &RawRecord) -> bool {
+        let number_match = self.number_set.contains(&rec.station_number.as_str());
+        let name_match = || match &rec.station_name {
+            Some(name) => self.name_set.iter().any(|pattern| like(pattern, name)),
+            None => false,
+        };
+        (number_match || name_match()) != self.exclude
+    }
+}
+
+/// Matches a string against a pattern with the syntax of SQL `LIKE`, case- and
+/// accent-insensitively.
+fn like(pattern: &str, s: &str) -> bool {
+    let p: Vec<char> = pattern.chars().map(fold_char).collect();
+    let s: Vec<char> = s.chars().map(fold_char).collect();
+
+    // Backtracking is only needed to the last `%` encountered
+    let (mut pi, mut si) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while si < s.len() {
+        match p.get(pi) {
+            Some('%') => {
+                backtrack = Some((pi, si));
+                pi += 1;
+            }
+            Some(ch) if *ch == '_' || *ch == s[si] => {
+                pi += 1;
+                si += 1;
+            }
+            _ => match backtrack {
+                Some((bpi, bsi)) => {
+                    backtrack = Some((bpi, bsi + 1));
+                    pi = bpi + 1;
+                    si = bsi + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    p[pi..].iter().all(|ch| *ch == '%')
+}
+
+/// Converts a character to lowercase without diacritics (for the Latin letters that are most
+/// likely to come up in station names).
+fn fold_char(ch: char) -> char {
+    match ch.to_lowercase().next().unwrap_or(ch) {
+        'á' | 'à' | 'â' | 'ä' | 'ã' | 'å' | 'ă' | 'ą' => 'a',
+        'ç' | 'ć' | 'č' => 'c',
+        'ď' => 'd',
+        'é' | 'è' | 'ê' | 'ë' | 'ě' | 'ę' => 'e',
+        'í' | 'ì' | 'î' | 'ï' => 'i',
+        'ĺ' | 'ľ' | 'ł' => 'l',
+        'ñ' | 'ń' | 'ň' => 'n',
+        'ó' | 'ò' | 'ô' | 'ö' | 'õ' | 'ő' | 'ø' => 'o',
+        'ŕ' | 'ř' => 'r',
+        'ś' | 'š' | 'ş' => 's',
+        'ť' | 'ţ' => 't',
+        'ú' | 'ù' | 'û' | 'ü' | 'ű' | 'ů' => 'u',
+        'ý' | 'ÿ' => 'y',
+        'ź' | 'ż' | 'ž' => 'z',
+        other => other,
     }
 }
@@ -0,0 +1,326 @@
+/*
+    Created by Zoltan Kovari, 2025.
+
+    Licensed under the Apache License, Version 2.0
+    http://www.apache.org/licenses/LICENSE-2.0
+    (see LICENSE.txt)
+*/
+
+use super::*;
+
+use crate::field::test::TEST_HEADER;
+use crate::field::{FieldConfig, FieldFilter};
+use crate::format::Delimiter;
+
+use std::str::FromStr;
+
+pub(crate) const TEST_CSV: &str = include_str!("../../tests/HABP_10M_SYNOP_20250110140805.csv");
+
+// Checking that every line of the sample file survives a round trip in the default format.
+#[test]
+fn test_rawrecord_roundtrip() {
+    let format = CsvFormat::default();
+    let mut lines = TEST_CSV.split_terminator("\r\n");
+    assert_eq!(lines.next().unwrap(), TEST_HEADER);
+
+    for line in lines {
+        let record = RawRecord::from_csv(line, &format).unwrap();
+        assert_eq!(record.to_csv(&format), line);
+    }
+}
+
+// Checking the parsed values, including the mapping of missing values.
+#[test]
+fn test_rawrecord_from_csv() {
+    let format = CsvFormat::default();
+    let line = TEST_CSV.split_terminator("\r\n").nth(2).unwrap();
+    let record = RawRecord::from_csv(line, &format).unwrap();
+    assert_eq!(record.get(Field::Time), Some("202501101350"));
+    assert_eq!(record.get(Field::StationNumber), Some("44527"));
+    assert_eq!(record.get(Field::StationName), Some("Budapest Pestszentlőrinc"));
+    assert_eq!(record.get(Field::Temp), Some("4.3"));
+    assert_eq!(record.get(Field::Q_Temp), None);
+    assert_eq!(record.get(Field::GammaRad), None);
+    assert_eq!(record.get(Field::EOR), Some("EOR"));
+
+    // Line terminator is ignored
+    let terminated = format!("{}\r\n", line);
+    assert_eq!(RawRecord::from_csv(&terminated, &format).unwrap(), record);
+}
+
+// Checking other formats, converting from the default one.
+#[test]
+fn test_rawrecord_formats() {
+    let line = TEST_CSV.split_terminator("\r\n").nth(2).unwrap();
+    let record = RawRecord::from_csv(line, &CsvFormat::default()).unwrap();
+
+    let ffilter = FieldFilter::new(
+        [Field::Temp, Field::Q_Temp, Field::GammaRad],
+        [Field::Elevation],
+    );
+    let fconfig = FieldConfig::new(true, false, false, true, ffilter.as_ref());
+
+    for (alignment, delim, missing, expected) in [
+        (
+            false,
+            ';',
+            MissingValue::Minus999,
+            "202501101350;44527;Budapest Pestszentlőrinc;47.4292;19.1822;4.3;;-999;EOR",
+        ),
+        (
+            false,
+            ',',
+            MissingValue::Empty,
+            "202501101350,44527,Budapest Pestszentlőrinc,47.4292,19.1822,4.3,,,EOR",
+        ),
+        (
+            false,
+            '\t',
+            MissingValue::Null,
+            "202501101350\t44527\tBudapest Pestszentlőrinc\t47.4292\t19.1822\t4.3\tnull\tnull\tEOR",
+        ),
+        (
+            true,
+            ' ',
+            MissingValue::Null,
+            "202501101350         44527 Budapest Pestszentlőrinc                  47.4292   19.1822   4.3 null     null EOR",
+        ),
+    ] {
+        let format = CsvFormat {
+            alignment,
+            missing,
+            delimiter: Delimiter::try_from(delim).unwrap(),
+            field_config: fconfig.clone(),
+        };
+        let output = record.to_csv(&format);
+        assert_eq!(output, expected);
+
+        let parsed = RawRecord::from_csv(&output, &format).unwrap();
+        for f in format.field_config.fields() {
+            assert_eq!(parsed.get(*f), record.get(*f));
+        }
+        assert_eq!(parsed.get(Field::Rain), None);
+    }
+}
+
+// Checking the parsing errors.
+#[test]
+fn test_rawrecord_errors() {
+    let format = CsvFormat::from_str("Time;StationNumber;t;EOR").unwrap();
+    for (line, eexpected) in [
+        (
+            "202501101350;44527;4.3",
+            ParseRecordError::FieldCount {
+                expected: 4,
+                found: 3,
+                column: 23,
+            },
+        ),
+        (
+            "202501101350;44527;4.3;EOR;",
+            ParseRecordError::FieldCount {
+                expected: 4,
+                found: 5,
+                column: 28,
+            },
+        ),
+        (
+            ";44527;4.3;EOR",
+            ParseRecordError::MissingMandatory {
+                field: Field::Time,
+                column: 1,
+            },
+        ),
+        (
+            "202501101350;-999;4.3;EOR",
+            ParseRecordError::MissingMandatory {
+                field: Field::StationNumber,
+                column: 14,
+            },
+        ),
+    ] {
+        assert_eq!(RawRecord::from_csv(line, &format).unwrap_err(), eexpected);
+    }
+
+    let format = CsvFormat {
+        alignment: true,
+        delimiter: Delimiter::try_from(' ').unwrap(),
+        ..format
+    };
+    assert_eq!(
+        RawRecord::from_csv("2025011013500         44527   4.3 EOR", &format).unwrap_err(),
+        ParseRecordError::Misaligned {
+            field: Field::Time,
+            column: 13,
+        },
+    );
+    assert_eq!(
+        RawRecord::from_csv("202501101350         44527", &format).unwrap_err(),
+        ParseRecordError::FieldCount {
+            expected: 4,
+            found: 2,
+            column: 27,
+        },
+    );
+}
+
+// Checking the conversion of every line of the sample file, and some of the values.
+#[test]
+fn test_metrecord_try_from() {
+    let format = CsvFormat::default();
+    for line in TEST_CSV.split_terminator("\r\n").skip(1) {
+        let raw = RawRecord::from_csv(line, &format).unwrap();
+        let met = MetRecord::try_from(&raw).unwrap();
+        assert_eq!(met, MetRecord::try_from(raw).unwrap());
+    }
+
+    let line = TEST_CSV.split_terminator("\r\n").nth(2).unwrap();
+    let met = MetRecord::try_from(RawRecord::from_csv(line, &format).unwrap()).unwrap();
+    assert_eq!(met.time(), Timestamp::new(2025, 1, 10, 13, 50).unwrap());
+    assert_eq!(met.station_number, 44527);
+    assert_eq!(met.station_name.as_deref(), Some("Budapest Pestszentlőrinc"));
+    assert_eq!(met.elevation, Some(138.1));
+    assert_eq!(met.temp, Some(4.3));
+    assert_eq!(met.q_temp, None);
+    assert_eq!(met.gamma_rad, None);
+    assert_eq!(met.visibility, Some(20000.0));
+    assert_eq!(met.eor.as_deref(), Some("EOR"));
+
+    assert_eq!(met.get(Field::StationNumber), Some(MetValue::Integer(44527)));
+    assert_eq!(met.get(Field::Temp), Some(MetValue::Float(4.3)));
+    assert_eq!(met.get(Field::Q_Temp), None);
+    assert_eq!(met.get(Field::EOR), Some(MetValue::Text("EOR")));
+
+    #[cfg(feature = "chrono")]
+    {
+        use chrono::{TimeZone, Utc};
+        assert_eq!(met.datetime(), Utc.with_ymd_and_hms(2025, 1, 10, 13, 50, 0).unwrap());
+    }
+}
+
+// Checking the (de)serialization of both record types.
+#[cfg(feature = "serde")]
+#[test]
+fn test_record_serde() {
+    let format = CsvFormat::default();
+    for line in TEST_CSV.split_terminator("\r\n").skip(1) {
+        let raw = RawRecord::from_csv(line, &format).unwrap();
+        let json = serde_json::to_string(&raw).unwrap();
+        assert_eq!(serde_json::from_str::<RawRecord>(&json).unwrap(), raw);
+
+        let met = MetRecord::try_from(raw).unwrap();
+        let json = serde_json::to_string(&met).unwrap();
+        assert_eq!(serde_json::from_str::<MetRecord>(&json).unwrap(), met);
+    }
+
+    let line = TEST_CSV.split_terminator("\r\n").nth(2).unwrap();
+    let met = MetRecord::try_from(RawRecord::from_csv(line, &format).unwrap()).unwrap();
+    let json = serde_json::to_value(&met).unwrap();
+    assert_eq!(json["time"], "202501101350");
+    assert_eq!(json["station_number"], 44527);
+    assert_eq!(json["temp"], 4.3);
+    assert!(json["gamma_rad"].is_null());
+}
+
+// Checking that conversion errors carry the station, field and value.
+#[test]
+fn test_metrecord_errors() {
+    let format = CsvFormat::from_str("Time;StationNumber;t;EOR").unwrap();
+    for (line, field, value) in [
+        ("202501101350;44527;4,3;EOR", Field::Temp, "4,3"),
+        ("202501101350;44527a;4.3;EOR", Field::StationNumber, "44527a"),
+        ("2025-01-10;44527;4.3;EOR", Field::Time, "2025-01-10"),
+        ("202502301350;44527;4.3;EOR", Field::Time, "202502301350"),
+        ("202501101361;44527;4.3;EOR", Field::Time, "202501101361"),
+    ] {
+        let raw = RawRecord::from_csv(line, &format).unwrap();
+        let e = MetRecord::try_from(raw).unwrap_err();
+        assert_eq!(e.field(), field);
+        assert_eq!(e.value(), value);
+        assert!(line.contains(e.station_number()));
+    }
+
+    let raw = RawRecord::from_csv("202501101350;44527;x;EOR", &format).unwrap();
+    let e = MetRecord::try_from(raw).unwrap_err();
+    assert!(matches!(e.reason(), ParseValueError::Float(_)));
+    assert_eq!(
+        e.to_string(),
+        "failed to convert record of station 44527, invalid value \"x\" for `t`: invalid float literal",
+    );
+
+    // Accepted by `f64::from_str`, but not valid ODP values
+    for value in ["NaN", "inf", "-infinity"] {
+        let line = format!("202501101350;44527;{};EOR", value);
+        let raw = RawRecord::from_csv(&line, &format).unwrap();
+        let e = MetRecord::try_from(raw).unwrap_err();
+        assert_eq!(e.reason(), &ParseValueError::NonFinite);
+        assert_eq!(e.value(), value);
+    }
+}
+
+// Checking the `LIKE` pattern matching.
+#[test]
+fn test_like() {
+    for (pattern, s) in [
+        ("Szeged", "Szeged"),
+        ("szeged", "SZEGED"),
+        ("_zeged", "Szeged"),
+        ("Budapest%", "Budapest Pestszentlőrinc"),
+        ("%pestszentlorinc", "Budapest Pestszentlőrinc"),
+        ("%Pest%", "Budapest Pestszentlőrinc"),
+        ("B%t%c", "Budapest Pestszentlőrinc"),
+        ("%", ""),
+        ("Bataapati_Vadaszhaz", "Bátaapáti Vadászház"),
+        ("%%repulőter", "Szentes repülőtér"),
+    ] {
+        assert!(like(pattern, s), "\"{pattern}\" should match \"{s}\"");
+    }
+
+    for (pattern, s) in [
+        ("Szeged", "Szegedi"),
+        ("_Szeged", "Szeged"),
+        ("Budapest", "Budapest Pestszentlőrinc"),
+        ("%Pest", "Budapest Pestszentlőrinc"),
+        ("B%x%c", "Budapest Pestszentlőrinc"),
+        ("_", ""),
+    ] {
+        assert!(!like(pattern, s), "\"{pattern}\" should not match \"{s}\"");
+    }
+}
+
+// Checking `RecordFilter` parsing and filtering on the sample file.
+#[test]
+fn test_record_filter() {
+    let format = CsvFormat::default();
+    let records: Vec<RawRecord> = TEST_CSV
+        .split_terminator("\r\n")
+        .skip(1)
+        .map(|line| RawRecord::from_csv(line, &format).unwrap())
+        .collect();
+    let count = |filter: &RecordFilter| records.iter().filter(|r| filter.filter(r)).count();
+
+    let filter = RecordFilter::from_str("44527, Szarvas", false).unwrap().unwrap();
+    assert_eq!(filter.number_set, ["44527"]);
+    assert_eq!(filter.name_set, ["Szarvas"]);
+    assert_eq!(count(&filter), 4);
+
+    let filter = RecordFilter::from_str("budapest%", false).unwrap().unwrap();
+    let budapest = count(&filter);
+    assert!(budapest > 0);
+    let filter = RecordFilter::from_str("budapest%", true).unwrap().unwrap();
+    assert_eq!(count(&filter), records.len() - budapest);
+
+    let filter = RecordFilter::from_str("Pestszentlorinc,%Pestszentlorinc", false).unwrap();
+    assert_eq!(count(&filter.unwrap()), 2);
+
+    assert_eq!(RecordFilter::from_str("", false), Ok(None));
+    assert_eq!(RecordFilter::from_str("  ", true), Ok(None));
+    assert_eq!(
+        RecordFilter::from_str("44527,,Szarvas", false),
+        Err(ParseRecordFilterError(6)),
+    );
+    assert_eq!(
+        RecordFilter::from_str("44527, ", false),
+        Err(ParseRecordFilterError(6)),
+    );
+}
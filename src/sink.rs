@@ -0,0 +1,337 @@
+/*
+    Created by Zoltan Kovari, 2025.
+
+    Licensed under the Apache License, Version 2.0
+    http://www.apache.org/licenses/LICENSE-2.0
+    (see LICENSE.txt)
+*/
+
+//! Destinations for records, so that one input can be written to several outputs in one pass.
+
+use crate::copy::*;
+use crate::csv::{write_header, write_record, CsvAppender};
+use crate::error::*;
+use crate::field::FieldConfig;
+use crate::format::CsvFormat;
+use crate::record::*;
+use crate::sql::*;
+
+use std::cell::OnceCell;
+use std::fmt;
+use std::fmt::Display;
+use std::io::Write;
+
+#[cfg(test)]
+mod test;
+
+/// Kinds of outputs that can be named in a configuration or on the command line.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum SinkKind {
+    /// CSV, see `CsvSink`.
+    Csv,
+    /// SQL script, see `SqlSink`.
+    Sql(Dialect),
+    /// psql script with `COPY`, see `CopySink`.
+    Copy(CopyFormat),
+    /// SQLite database file, see `DbSink`.
+    #[cfg(feature = "sqlite")]
+    Sqlite,
+}
+impl Display for SinkKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Csv => write!(f, "csv"),
+            Self::Sql(dialect) => write!(f, "sql-{}", dialect),
+            Self::Copy(format) => write!(f, "copy-{}", format),
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite => write!(f, "sqlite"),
+        }
+    }
+}
+impl TryFrom<&str> for SinkKind {
+    type Error = ParseSinkKindError;
+
+    /// Accepts the names printed by `Display`.
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        match s {
+            "csv" => Ok(Self::Csv),
+            "sql-sqlite" => Ok(Self::Sql(Dialect::Sqlite)),
+            "sql-postgres" => Ok(Self::Sql(Dialect::Postgres)),
+            "copy-text" => Ok(Self::Copy(CopyFormat::Text)),
+            "copy-csv" => Ok(Self::Copy(CopyFormat::Csv)),
+            #[cfg(feature = "sqlite")]
+            "sqlite" => Ok(Self::Sqlite),
+            _ => Err(ParseSinkKindError(s.to_string())),
+        }
+    }
+}
+
+/// A record passed to the sinks, converted to `MetRecord` only if a sink needs it (and at most
+/// once).
+pub struct SinkRecord {
+    raw: RawRecord,
+    line: Option<usize>,
+    met: OnceCell<Result<MetRecord, TryFromRawRecordError>>,
+}
+impl SinkRecord {
+    pub fn new(raw: RawRecord) -> Self {
+        Self {
+            raw,
+            line: None,
+            met: OnceCell::new(),
+        }
+    }
+
+    /// Sets the number of the input line, which is included in the conversion error.
+    pub fn at_line(self, line: usize) -> Self {
+        Self {
+            line: Some(line),
+            ..self
+        }
+    }
+
+    pub fn raw(&self) -> &RawRecord {
+        &self.raw
+    }
+
+    /// Returns the converted record, the conversion error is cloned for every caller.
+    pub fn met(&self) -> Result<&MetRecord, TryFromRawRecordError> {
+        self.met
+            .get_or_init(|| {
+                MetRecord::try_from(&self.raw).map_err(|e| match self.line {
+                    Some(line) => e.at_line(line),
+                    None => e,
+                })
+            })
+            .as_ref()
+            .map_err(Clone::clone)
+    }
+}
+impl From<RawRecord> for SinkRecord {
+    fn from(raw: RawRecord) -> Self {
+        Self::new(raw)
+    }
+}
+
+/// A destination that records can be written to one by one.
+pub trait RecordSink {
+    /// Writes a single record.
+    fn write(&mut self, record: &SinkRecord) -> Result<(), SinkError>;
+
+    /// Writes out anything pending, after the last record. The sink should not be used after
+    /// this.
+    fn finish(&mut self) -> Result<(), SinkError>;
+}
+impl<S: RecordSink + ?Sized> RecordSink for Box<S> {
+    fn write(&mut self, record: &SinkRecord) -> Result<(), SinkError> {
+        (**self).write(record)
+    }
+
+    fn finish(&mut self) -> Result<(), SinkError> {
+        (**self).finish()
+    }
+}
+
+/// Writes CSV in its own format (see `csv::write_csv`).
+pub struct CsvSink<W: Write> {
+    writer: W,
+    format: CsvFormat,
+    line: usize,
+}
+impl<W: Write> CsvSink<W> {
+    /// Constructs a sink, writing the header immediately.
+    pub fn new(mut writer: W, format: CsvFormat) -> Result<Self, SinkError> {
+        write_header(&mut writer, &format)?;
+        Ok(Self {
+            writer,
+            format,
+            line: 1,
+        })
+    }
+
+    /// Constructs a sink that does not write the header, e.g. for appending to a file.
+    pub fn without_header(writer: W, format: CsvFormat) -> Self {
+        Self {
+            writer,
+            format,
+            line: 0,
+        }
+    }
+}
+impl<W: Write> RecordSink for CsvSink<W> {
+    fn write(&mut self, record: &SinkRecord) -> Result<(), SinkError> {
+        self.line += 1;
+        write_record(&mut self.writer, record.raw(), &self.format, self.line)?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), SinkError> {
+        self.writer.flush().map_err(|e| WriteCsvError {
+            line: self.line,
+            source: e,
+        })?;
+        Ok(())
+    }
+}
+
+/// Appends new records to existing CSV content with `CsvAppender`.
+impl<F: Write> RecordSink for CsvAppender<F> {
+    fn write(&mut self, record: &SinkRecord) -> Result<(), SinkError> {
+        CsvAppender::write(self, record.raw())?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), SinkError> {
+        self.flush()?;
+        Ok(())
+    }
+}
+
+/// Writes an SQL script with `SqlWriter`.
+pub struct SqlSink<W: Write> {
+    writer: Option<SqlWriter<W>>,
+}
+impl<W: Write> SqlSink<W> {
+    /// Constructs a sink, writing the start of the script immediately.
+    pub fn new(
+        writer: W,
+        field_config: &FieldConfig,
+        dialect: Dialect,
+    ) -> Result<Self, SinkError> {
+        Ok(Self {
+            writer: Some(SqlWriter::new(writer, field_config, dialect)?),
+        })
+    }
+}
+impl<W: Write> RecordSink for SqlSink<W> {
+    fn write(&mut self, record: &SinkRecord) -> Result<(), SinkError> {
+        if let Some(writer) = &mut self.writer {
+            writer.write_met(record.met()?)?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), SinkError> {
+        if let Some(writer) = self.writer.take() {
+            writer.finish()?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes a psql script that creates the `data` table (in the PostgreSQL dialect), and loads the
+/// records with `COPY ... FROM STDIN`, see `CopyWriter`.
+pub struct CopySink<W: Write> {
+    writer: Option<CopyWriter<W>>,
+}
+impl<W: Write> CopySink<W> {
+    /// Constructs a sink, writing the statements immediately.
+    pub fn new(
+        mut writer: W,
+        field_config: &FieldConfig,
+        format: CopyFormat,
+    ) -> Result<Self, SinkError> {
+        write!(
+            writer,
+            "{}\n{}\n",
+            create_table_sql(field_config, Dialect::Postgres),
+            copy_statement(field_config, format),
+        )?;
+        Ok(Self {
+            writer: Some(CopyWriter::new(writer, field_config, format)),
+        })
+    }
+}
+impl<W: Write> RecordSink for CopySink<W> {
+    fn write(&mut self, record: &SinkRecord) -> Result<(), SinkError> {
+        if let Some(writer) = &mut self.writer {
+            writer.write(record.met()?)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the end-of-data marker.
+    fn finish(&mut self) -> Result<(), SinkError> {
+        if let Some(writer) = self.writer.take() {
+            let mut writer = writer.finish()?;
+            writeln!(writer, "\\.")?;
+            writer.flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// Inserts the records into a `Database`, in batches of `BATCH_SIZE` (so that every batch is a
+/// single transaction, see `Database::insert`).
+#[cfg(feature = "sqlite")]
+pub struct DbSink {
+    db: crate::db::Database,
+    batch: Vec<MetRecord>,
+}
+#[cfg(feature = "sqlite")]
+impl DbSink {
+    pub fn new(db: crate::db::Database) -> Self {
+        Self {
+            db,
+            batch: Vec::with_capacity(crate::db::BATCH_SIZE),
+        }
+    }
+}
+#[cfg(feature = "sqlite")]
+impl RecordSink for DbSink {
+    fn write(&mut self, record: &SinkRecord) -> Result<(), SinkError> {
+        self.batch.push(record.met()?.clone());
+        if self.batch.len() == crate::db::BATCH_SIZE {
+            self.db.insert(self.batch.drain(..))?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), SinkError> {
+        self.db.insert(self.batch.drain(..))?;
+        Ok(())
+    }
+}
+
+/// Passes every record to each of its sinks, in order.
+#[derive(Default)]
+pub struct Tee<'a> {
+    sinks: Vec<Box<dyn RecordSink + 'a>>,
+}
+impl<'a> Tee<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a sink at the end.
+    pub fn push(&mut self, sink: impl RecordSink + 'a) {
+        self.sinks.push(Box::new(sink));
+    }
+
+    /// Number of sinks.
+    pub fn len(&self) -> usize {
+        self.sinks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sinks.is_empty()
+    }
+}
+impl RecordSink for Tee<'_> {
+    /// Stops at the first error.
+    fn write(&mut self, record: &SinkRecord) -> Result<(), SinkError> {
+        self.sinks.iter_mut().try_for_each(|s| s.write(record))
+    }
+
+    /// Every sink is finished, even if some of them fail, the first error is returned.
+    fn finish(&mut self) -> Result<(), SinkError> {
+        let mut result = Ok(());
+        for sink in &mut self.sinks {
+            let r = sink.finish();
+            if result.is_ok() {
+                result = r;
+            }
+        }
+        result
+    }
+}
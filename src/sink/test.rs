@@ -0,0 +1,124 @@
+/*
+    Created by Zoltan Kovari, 2025.
+
+    Licensed under the Apache License, Version 2.0
+    http://www.apache.org/licenses/LICENSE-2.0
+    (see LICENSE.txt)
+*/
+
+use super::*;
+
+use crate::csv::{convert_csv, convert_csv_into};
+use crate::field::{Field, FieldFilter};
+use crate::format::{Delimiter, MissingValue};
+use crate::record::test::TEST_CSV;
+
+// Checking that every sink of a `Tee` gets the same output as it would get on its own.
+#[test]
+fn test_tee() {
+    let ffilter = FieldFilter::new([Field::Temp], std::iter::empty());
+    let condensed = CsvFormat {
+        alignment: false,
+        missing: MissingValue::Empty,
+        delimiter: Delimiter::try_from(',').unwrap(),
+        field_config: FieldConfig::new(false, false, false, false, ffilter.as_ref()),
+    };
+
+    let (mut csv1, mut csv2, mut sql, mut copy) = (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+    let mut tee = Tee::new();
+    tee.push(CsvSink::new(&mut csv1, condensed.clone()).unwrap());
+    tee.push(CsvSink::new(&mut csv2, CsvFormat::default()).unwrap());
+    tee.push(SqlSink::new(&mut sql, &condensed.field_config, Dialect::Sqlite).unwrap());
+    tee.push(CopySink::new(&mut copy, &condensed.field_config, CopyFormat::Text).unwrap());
+    assert_eq!(tee.len(), 4);
+
+    let count = convert_csv_into(TEST_CSV.as_bytes(), None, &mut tee).unwrap();
+    assert_eq!(count, TEST_CSV.lines().count() - 1);
+    drop(tee);
+
+    let mut expected = Vec::new();
+    convert_csv(TEST_CSV.as_bytes(), &mut expected, None, &condensed).unwrap();
+    assert_eq!(csv1, expected);
+    assert_eq!(String::from_utf8(csv2).unwrap(), TEST_CSV);
+
+    let (records, _) = crate::csv::read_csv(TEST_CSV.as_bytes(), None).unwrap();
+    let records: Vec<RawRecord> = records.map(|r| r.unwrap()).collect();
+    let mut expected = Vec::new();
+    write_sql(records, &mut expected, &condensed.field_config, Dialect::Sqlite).unwrap();
+    assert_eq!(sql, expected);
+
+    let copy = String::from_utf8(copy).unwrap();
+    assert!(copy.starts_with("CREATE TABLE IF NOT EXISTS \"data\" (\n"));
+    assert!(copy.contains("\nCOPY \"data\" (\"Time\", \"StationNumber\", \"t\") FROM STDIN"));
+    assert!(copy.contains("\n2025-01-10 13:50:00\t44527\t4.3\n"));
+    assert!(copy.ends_with("\n\\.\n"));
+}
+
+// Checking that a conversion error of one record stops the pass, and CSV needs no conversion.
+#[test]
+fn test_sink_errors() {
+    let input = "Time;StationNumber;t;EOR\n202501101350;44527;x;EOR\n";
+
+    let mut output = Vec::new();
+    let mut sink = CsvSink::without_header(&mut output, CsvFormat::default());
+    assert_eq!(convert_csv_into(input.as_bytes(), None, &mut sink).unwrap(), 1);
+    assert!(!String::from_utf8(output).unwrap().starts_with("Time"));
+
+    let mut tee = Tee::new();
+    tee.push(CsvSink::new(Vec::new(), CsvFormat::default()).unwrap());
+    tee.push(SqlSink::new(Vec::new(), &FieldConfig::default(), Dialect::Sqlite).unwrap());
+    match convert_csv_into(input.as_bytes(), None, &mut tee) {
+        Err(SinkError::Convert(e)) => {
+            assert_eq!(e.field(), Field::Temp);
+            assert_eq!(e.line(), Some(2));
+            assert_eq!(
+                e.to_string(),
+                "failed to convert record of station 44527 at line 2, invalid value \"x\" for \
+                 `t`: invalid float literal",
+            );
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn test_db_sink() {
+    let db = crate::db::Database::open_in_memory(&FieldConfig::default()).unwrap();
+    let mut sink = DbSink::new(db);
+    let count = convert_csv_into(TEST_CSV.as_bytes(), None, &mut sink).unwrap();
+
+    let rows: i64 = sink
+        .db
+        .connection()
+        .query_row("SELECT COUNT(*) FROM data", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(rows as usize, count);
+}
+
+// Checking that the records of every batch are inserted, including the last partial one.
+#[cfg(feature = "sqlite")]
+#[test]
+fn test_db_sink_batches() {
+    // The sample records on four different days, more than one batch
+    let mut lines = TEST_CSV.split_terminator("\r\n");
+    let mut csv = format!("{}\r\n", lines.next().unwrap());
+    let lines: Vec<&str> = lines.collect();
+    for day in ["20250110", "20250111", "20250112", "20250113"] {
+        for line in &lines {
+            csv.push_str(&format!("{}{}\r\n", day, &line[8..]));
+        }
+    }
+
+    let db = crate::db::Database::open_in_memory(&FieldConfig::default()).unwrap();
+    let mut sink = DbSink::new(db);
+    let count = convert_csv_into(csv.as_bytes(), None, &mut sink).unwrap();
+    assert!(count > crate::db::BATCH_SIZE && count % crate::db::BATCH_SIZE != 0);
+
+    let rows: i64 = sink
+        .db
+        .connection()
+        .query_row("SELECT COUNT(*) FROM data", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(rows as usize, count);
+}
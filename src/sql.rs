@@ -0,0 +1,208 @@
+/*
+    Created by Zoltan Kovari, 2025.
+
+    Licensed under the Apache License, Version 2.0
+    http://www.apache.org/licenses/LICENSE-2.0
+    (see LICENSE.txt)
+*/
+
+//! Functions for emitting SQL scripts, to be reviewed and applied manually.
+
+use crate::error::*;
+use crate::field::field_properties::FieldType;
+use crate::field::{Field, FieldConfig};
+use crate::record::{MetRecord, MetValue, RawRecord};
+
+use std::fmt;
+use std::fmt::Display;
+use std::io::Write;
+
+#[cfg(test)]
+mod test;
+
+/// Name of the table that holds the records.
+pub const TABLE_NAME: &str = "data";
+
+/// Number of rows in a single `INSERT` statement.
+const BATCH_SIZE: usize = 500;
+
+/// SQL dialects supported in the scripts.
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Dialect {
+    #[default]
+    Sqlite,
+    Postgres,
+}
+impl Display for Dialect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", match self {
+            Self::Sqlite => "sqlite",
+            Self::Postgres => "postgres",
+        })
+    }
+}
+impl TryFrom<&str> for Dialect {
+    type Error = ParseDialectError;
+
+    /// Accepts the names printed by `Display`, also "postgresql" (case-insensitively).
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        match s.to_ascii_lowercase().as_str() {
+            "sqlite" => Ok(Self::Sqlite),
+            "postgres" | "postgresql" => Ok(Self::Postgres),
+            _ => Err(ParseDialectError(s.to_string())),
+        }
+    }
+}
+
+/// Returns the statement that creates the `data` table (if it does not exist yet).
+///
+/// The table has a column for every selected field except `EOR` (which is a constant marker),
+/// named after the field title. The primary key is (`Time`, `StationNumber`), and `Time` is
+/// meant to be UTC.
+pub fn create_table_sql(field_config: &FieldConfig, dialect: Dialect) -> String {
+    let mut sql = format!("CREATE TABLE IF NOT EXISTS {} (\n", quote_ident(TABLE_NAME));
+    for field in field_config.fields() {
+        if let Some(ty) = column_type(*field, dialect) {
+            let constraint = match field.field_type() {
+                FieldType::Mandatory => " NOT NULL",
+                _ => "",
+            };
+            sql.push_str(&format!("    {} {}{},\n", quote_ident(field.title()), ty, constraint));
+        }
+    }
+    sql.push_str(&format!(
+        "    PRIMARY KEY ({}, {})\n);\n",
+        quote_ident(Field::Time.title()),
+        quote_ident(Field::StationNumber.title()),
+    ));
+    sql
+}
+
+/// Column type of a field in the dialect, `None` for `EOR`.
+pub(crate) fn column_type(field: Field, dialect: Dialect) -> Option<&'static str> {
+    let real = match dialect {
+        Dialect::Sqlite => "REAL",
+        Dialect::Postgres => "DOUBLE PRECISION",
+    };
+    match field.field_type() {
+        FieldType::Mandatory if field == Field::Time => match dialect {
+            Dialect::Sqlite => Some("TEXT"),
+            Dialect::Postgres => Some("TIMESTAMP"),
+        },
+        FieldType::Mandatory => Some("INTEGER"),
+        FieldType::Info if field != Field::StationName => Some(real),
+        FieldType::Value => Some(real),
+        FieldType::EOR => None,
+        _ => Some("TEXT"),
+    }
+}
+
+/// Writes a script that creates the `data` table and inserts records into it.
+///
+/// The whole script is a single transaction, and the rows are inserted in batches, skipping
+/// those that are already present (`ON CONFLICT DO NOTHING`), so it can be applied repeatedly.
+/// `ON CONFLICT` needs SQLite 3.24 or PostgreSQL 9.5 at least.
+pub struct SqlWriter<W: Write> {
+    writer: W,
+    fields: Vec<Field>,
+    /// Value lists of the rows not written yet.
+    batch: Vec<String>,
+}
+impl<W: Write> SqlWriter<W> {
+    /// Writes the start of the script, including the `CREATE TABLE` statement.
+    ///
+    /// The dialect only affects the column types, the inserts are the same for both.
+    pub fn new(
+        mut writer: W,
+        field_config: &FieldConfig,
+        dialect: Dialect,
+    ) -> Result<Self, WriteSqlError> {
+        write!(writer, "BEGIN;\n\n{}\n", create_table_sql(field_config, dialect))?;
+        Ok(Self {
+            writer,
+            fields: field_config
+                .fields()
+                .filter(|f| column_type(**f, dialect).is_some())
+                .copied()
+                .collect(),
+            batch: Vec::with_capacity(BATCH_SIZE),
+        })
+    }
+
+    /// Adds a record to the current batch, writing the batch out when it is full.
+    ///
+    /// The record is converted to `MetRecord` first, so that only valid literals are written.
+    pub fn write(&mut self, record: &RawRecord) -> Result<(), WriteSqlError> {
+        self.write_met(&MetRecord::try_from(record)?)
+    }
+
+    /// Same as `write`, for an already converted record.
+    pub fn write_met(&mut self, record: &MetRecord) -> Result<(), WriteSqlError> {
+        let values: Vec<String> = self.fields.iter().map(|f| literal(record.get(*f))).collect();
+        self.batch.push(format!("({})", values.join(", ")));
+        if self.batch.len() == BATCH_SIZE {
+            self.write_batch()?;
+        }
+        Ok(())
+    }
+
+    /// Writes the last batch and the end of the script, then returns the writer.
+    pub fn finish(mut self) -> Result<W, WriteSqlError> {
+        self.write_batch()?;
+        writeln!(self.writer, "COMMIT;")?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn write_batch(&mut self) -> Result<(), WriteSqlError> {
+        if self.batch.is_empty() {
+            return Ok(());
+        }
+        writeln!(
+            self.writer,
+            "INSERT INTO {} ({}) VALUES\n{}\nON CONFLICT DO NOTHING;\n",
+            quote_ident(TABLE_NAME),
+            self.fields
+                .iter()
+                .map(|f| quote_ident(f.title()))
+                .collect::<Vec<String>>()
+                .join(", "),
+            self.batch.join(",\n"),
+        )?;
+        self.batch.clear();
+        Ok(())
+    }
+}
+
+/// Convenience routine to write a complete script with `SqlWriter`.
+pub fn write_sql(
+    records: impl IntoIterator<Item = RawRecord>,
+    writer: impl Write,
+    field_config: &FieldConfig,
+    dialect: Dialect,
+) -> Result<(), WriteSqlError> {
+    let mut sql = SqlWriter::new(writer, field_config, dialect)?;
+    for record in records {
+        sql.write(&record)?;
+    }
+    sql.finish()?;
+
+    Ok(())
+}
+
+/// Quotes an identifier, so that case is preserved.
+fn quote_ident(s: &str) -> String {
+    format!("\"{}\"", s.replace('"', "\"\""))
+}
+
+/// SQL literal of a value, non-finite numbers are written as `NULL`.
+fn literal(value: Option<MetValue>) -> String {
+    match value {
+        None => "NULL".to_string(),
+        Some(MetValue::Timestamp(ts)) => format!("'{}'", ts.to_iso_string()),
+        Some(MetValue::Integer(n)) => n.to_string(),
+        Some(MetValue::Float(x)) if x.is_finite() => x.to_string(),
+        Some(MetValue::Float(_)) => "NULL".to_string(),
+        Some(MetValue::Text(s)) => format!("'{}'", s.replace('\'', "''")),
+    }
+}
@@ -0,0 +1,123 @@
+/*
+    Created by Zoltan Kovari, 2025.
+
+    Licensed under the Apache License, Version 2.0
+    http://www.apache.org/licenses/LICENSE-2.0
+    (see LICENSE.txt)
+*/
+
+use super::*;
+
+use crate::csv::read_csv;
+use crate::format::Delimiter;
+use crate::record::test::TEST_CSV;
+
+// Checking the dialect names.
+#[test]
+fn test_dialect() {
+    for dialect in [Dialect::Sqlite, Dialect::Postgres] {
+        assert_eq!(Dialect::try_from(dialect.to_string().as_str()), Ok(dialect));
+    }
+    assert_eq!(Dialect::try_from("PostgreSQL"), Ok(Dialect::Postgres));
+    assert!(Dialect::try_from("mysql").is_err());
+}
+
+// Checking the column types in both dialects.
+#[test]
+fn test_create_table_sql() {
+    let header = "Time;StationNumber;StationName;t;Q_t;EOR";
+    let config = FieldConfig::from_header(header, Delimiter::default()).unwrap();
+    assert_eq!(
+        create_table_sql(&config, Dialect::Sqlite),
+        "\
+CREATE TABLE IF NOT EXISTS \"data\" (
+    \"Time\" TEXT NOT NULL,
+    \"StationNumber\" INTEGER NOT NULL,
+    \"StationName\" TEXT,
+    \"t\" REAL,
+    \"Q_t\" TEXT,
+    PRIMARY KEY (\"Time\", \"StationNumber\")
+);
+",
+    );
+    assert_eq!(
+        create_table_sql(&config, Dialect::Postgres),
+        "\
+CREATE TABLE IF NOT EXISTS \"data\" (
+    \"Time\" TIMESTAMP NOT NULL,
+    \"StationNumber\" INTEGER NOT NULL,
+    \"StationName\" TEXT,
+    \"t\" DOUBLE PRECISION,
+    \"Q_t\" TEXT,
+    PRIMARY KEY (\"Time\", \"StationNumber\")
+);
+",
+    );
+}
+
+// Checking the literals, and that the records are split into batches.
+#[test]
+fn test_write_sql() {
+    let header = "Time;StationNumber;StationName;t;EOR";
+    let config = FieldConfig::from_header(header, Delimiter::default()).unwrap();
+    let format = crate::format::CsvFormat {
+        field_config: config.clone(),
+        alignment: false,
+        ..Default::default()
+    };
+    let records = [
+        "202501101350;44527;Budapest Pestszentlőrinc;4.3;EOR",
+        "202501101350;13704;Sopron-Kuruc d'omb;-999;EOR",
+    ]
+    .map(|line| RawRecord::from_csv(line, &format).unwrap());
+
+    let mut output = Vec::new();
+    write_sql(records, &mut output, &config, Dialect::Postgres).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.starts_with("BEGIN;\n\nCREATE TABLE IF NOT EXISTS \"data\" (\n"));
+    assert!(output.ends_with(
+        "\
+INSERT INTO \"data\" (\"Time\", \"StationNumber\", \"StationName\", \"t\") VALUES
+('2025-01-10 13:50:00', 44527, 'Budapest Pestszentlőrinc', 4.3),
+('2025-01-10 13:50:00', 13704, 'Sopron-Kuruc d''omb', NULL)
+ON CONFLICT DO NOTHING;
+
+COMMIT;
+"
+    ));
+
+    // The sample file needs a single batch
+    let (records, csv_format) = read_csv(TEST_CSV.as_bytes(), None).unwrap();
+    let mut output = Vec::new();
+    let config_all = &csv_format.field_config;
+    write_sql(records.map(|r| r.unwrap()), &mut output, config_all, Dialect::Sqlite).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert_eq!(output.matches("INSERT INTO").count(), 1);
+    assert_eq!(output.matches("\n('").count(), TEST_CSV.lines().count() - 1);
+
+    // Invalid values are not written
+    let record = RawRecord::from_csv("202501101350;44527;X;x;EOR", &format).unwrap();
+    assert!(matches!(
+        write_sql([record], Vec::new(), &config, Dialect::Sqlite),
+        Err(WriteSqlError::Convert(_))
+    ));
+}
+
+// Checking that a script is accepted by SQLite, and can be applied repeatedly.
+#[cfg(feature = "sqlite")]
+#[test]
+fn test_write_sql_sqlite() {
+    let (records, format) = read_csv(TEST_CSV.as_bytes(), None).unwrap();
+    let records: Vec<RawRecord> = records.map(|r| r.unwrap()).collect();
+    let mut output = Vec::new();
+    write_sql(records.clone(), &mut output, &format.field_config, Dialect::Sqlite).unwrap();
+    let script = String::from_utf8(output).unwrap();
+
+    let conn = rusqlite::Connection::open_in_memory().unwrap();
+    conn.execute_batch(&script).unwrap();
+    conn.execute_batch(&script).unwrap();
+    let count: i64 = conn
+        .query_row("SELECT COUNT(*) FROM data", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(count as usize, records.len());
+}
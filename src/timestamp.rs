@@ -0,0 +1,236 @@
+/*
+    Created by Zoltan Kovari, 2025.
+
+    Licensed under the Apache License, Version 2.0
+    http://www.apache.org/licenses/LICENSE-2.0
+    (see LICENSE.txt)
+*/
+
+//! Timestamp type for the `Time` field.
+
+use crate::error::*;
+
+use std::fmt;
+use std::fmt::Display;
+use std::str::FromStr;
+
+#[cfg(test)]
+mod test;
+
+/// A point in time with minute resolution, always in UTC.
+///
+/// The string representation is `YYYYMMDDhhmm`, as used in the `Time` column of the ODP files
+/// (the daily files only have the date, see `from_str`).
+///
+/// With the `chrono` feature enabled, it can be converted to and from `chrono::DateTime<Utc>`
+/// (see `to_datetime`, and `MetRecord::datetime` for the records).
+/// With the `serde` feature enabled, it is (de)serialized as the string representation.
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Timestamp {
+    year: u16,
+    month: u8,
+    day: u8,
+    hour: u8,
+    minute: u8,
+}
+impl Timestamp {
+    /// Constructs an instance, checking that the date and time actually exist.
+    ///
+    /// The year must fit in four digits.
+    pub fn new(
+        year: u16,
+        month: u8,
+        day: u8,
+        hour: u8,
+        minute: u8,
+    ) -> Result<Self, ParseTimestampError> {
+        let valid = year <= 9999
+            && (1..=12).contains(&month)
+            && day >= 1
+            && day <= days_in_month(year, month)
+            && hour < 24
+            && minute < 60;
+        if !valid {
+            return Err(ParseTimestampError::InvalidDate(format!(
+                "{:04}-{:02}-{:02} {:02}:{:02}",
+                year, month, day, hour, minute
+            )));
+        }
+
+        Ok(Self {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+        })
+    }
+
+    /// Year, at most four digits.
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    /// Month of the year, starting from 1.
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    /// Day of the month, starting from 1.
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+
+    /// Hour of the day (0-23).
+    pub fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    /// Minute of the hour (0-59).
+    pub fn minute(&self) -> u8 {
+        self.minute
+    }
+
+    /// Seconds elapsed since the Unix epoch (1970-01-01 00:00 UTC).
+    pub fn unix_time(&self) -> i64 {
+        let days = days_from_civil(self.year, self.month, self.day);
+        days * 86400 + i64::from(self.hour) * 3600 + i64::from(self.minute) * 60
+    }
+
+    /// Constructs an instance from seconds elapsed since the Unix epoch, truncating to the
+    /// minute.
+    ///
+    /// Returns `None` if the result would be outside of the four-digit year range.
+    pub fn from_unix_time(secs: i64) -> Option<Self> {
+        let days = secs.div_euclid(86400);
+        let rem = secs.rem_euclid(86400);
+        let (year, month, day) = civil_from_days(days)?;
+        Self::new(year, month, day, (rem / 3600) as u8, (rem % 3600 / 60) as u8).ok()
+    }
+
+    /// Returns the same point in time as a chrono type.
+    #[cfg(feature = "chrono")]
+    pub fn to_datetime(&self) -> chrono::DateTime<chrono::Utc> {
+        // Always valid, as the range of `Timestamp` is narrower
+        chrono::DateTime::from_timestamp(self.unix_time(), 0).unwrap()
+    }
+
+    /// Returns the ISO 8601 representation with a space separator, as commonly used in SQL
+    /// (`YYYY-MM-DD hh:mm:ss`, the seconds are always zero).
+    pub fn to_iso_string(&self) -> String {
+        format!(
+            "{:04}-{:02}-{:02} {:02}:{:02}:00",
+            self.year, self.month, self.day, self.hour, self.minute
+        )
+    }
+}
+impl Display for Timestamp {
+    /// Prints the `YYYYMMDDhhmm` representation.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:04}{:02}{:02}{:02}{:02}",
+            self.year, self.month, self.day, self.hour, self.minute
+        )
+    }
+}
+impl FromStr for Timestamp {
+    type Err = ParseTimestampError;
+
+    /// Tries to parse the `YYYYMMDDhhmm` representation, or `YYYYMMDD` (midnight) as in the
+    /// daily data.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if ![8, 12].contains(&s.len()) || !s.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(ParseTimestampError::InvalidFormat(s.to_string()));
+        }
+
+        // Cannot fail after the check above, hours and minutes are 0 if missing
+        let num = |range: std::ops::Range<usize>| {
+            s.get(range).map_or(0, |n| n.parse::<u16>().unwrap())
+        };
+        Self::new(
+            num(0..4),
+            num(4..6) as u8,
+            num(6..8) as u8,
+            num(8..10) as u8,
+            num(10..12) as u8,
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Timestamp {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Timestamp {
+    /// Fails the same way as `from_str`.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<Timestamp> for chrono::DateTime<chrono::Utc> {
+    /// Calls `to_datetime`.
+    fn from(ts: Timestamp) -> Self {
+        ts.to_datetime()
+    }
+}
+#[cfg(feature = "chrono")]
+impl TryFrom<chrono::DateTime<chrono::Utc>> for Timestamp {
+    type Error = ParseTimestampError;
+
+    /// Seconds are truncated, fails only if the year does not fit in four digits.
+    fn try_from(dt: chrono::DateTime<chrono::Utc>) -> Result<Self, Self::Error> {
+        Self::from_unix_time(dt.timestamp())
+            .ok_or_else(|| ParseTimestampError::InvalidDate(dt.to_string()))
+    }
+}
+
+fn is_leap_year(year: u16) -> bool {
+    (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400)
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since the Unix epoch for a (proleptic Gregorian) date.
+///
+/// Based on the algorithm described by Howard Hinnant:
+/// http://howardhinnant.github.io/date_algorithms.html
+fn days_from_civil(year: u16, month: u8, day: u8) -> i64 {
+    let y = i64::from(year) - i64::from(month <= 2);
+    let m = i64::from(month);
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+/// Inverse of `days_from_civil`, returns `None` outside of the four-digit year range.
+fn civil_from_days(days: i64) -> Option<(u16, u8, u8)> {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u8;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    u16::try_from(year)
+        .ok()
+        .filter(|y| *y <= 9999)
+        .map(|y| (y, month, day))
+}
@@ -0,0 +1,114 @@
+/*
+    Created by Zoltan Kovari, 2025.
+
+    Licensed under the Apache License, Version 2.0
+    http://www.apache.org/licenses/LICENSE-2.0
+    (see LICENSE.txt)
+*/
+
+use super::*;
+
+// Checking parsing and display.
+#[test]
+fn test_timestamp_from_str() {
+    for s in ["202501101350", "202402291200", "200002292359", "000101010000", "999912312359"] {
+        assert_eq!(Timestamp::from_str(s).unwrap().to_string(), s);
+    }
+
+    // Dates of the daily data
+    assert_eq!(Timestamp::from_str("20250110").unwrap().to_string(), "202501100000");
+
+    for s in ["20250110135", "2025011013500", "2025-01-1013", "+20501101350", "2025011", ""] {
+        assert_eq!(
+            Timestamp::from_str(s).unwrap_err(),
+            ParseTimestampError::InvalidFormat(s.to_string()),
+        );
+    }
+
+    // Impossible dates and times
+    for s in [
+        "202501101361",
+        "202501102400",
+        "202502300000",
+        "202502290000",
+        "190002290000",
+        "202504310000",
+        "202513010000",
+        "202500010000",
+        "202501000000",
+    ] {
+        assert!(matches!(
+            Timestamp::from_str(s),
+            Err(ParseTimestampError::InvalidDate(_))
+        ));
+    }
+}
+
+// Checking the ordering, which follows the chronological order.
+#[test]
+fn test_timestamp_ord() {
+    let mut v: Vec<Timestamp> = ["202501101400", "202412312359", "202501101350", "202501011400"]
+        .into_iter()
+        .map(|s| s.parse().unwrap())
+        .collect();
+    v.sort();
+    let v: Vec<String> = v.into_iter().map(|ts| ts.to_string()).collect();
+    assert_eq!(v, ["202412312359", "202501011400", "202501101350", "202501101400"]);
+}
+
+// Checking the conversion to and from Unix time.
+#[test]
+fn test_timestamp_unix_time() {
+    for (s, secs) in [
+        ("197001010000", 0),
+        ("196912312359", -60),
+        ("200002290000", 951782400),
+        ("202501101350", 1736517000),
+    ] {
+        let ts = Timestamp::from_str(s).unwrap();
+        assert_eq!(ts.unix_time(), secs);
+        assert_eq!(Timestamp::from_unix_time(secs), Some(ts));
+    }
+
+    assert_eq!(
+        Timestamp::from_str("202501101350").unwrap().to_iso_string(),
+        "2025-01-10 13:50:00",
+    );
+
+    // Seconds are truncated
+    assert_eq!(
+        Timestamp::from_unix_time(1736517059).unwrap().to_string(),
+        "202501101350",
+    );
+
+    // Out of range
+    let max = Timestamp::from_str("999912312359").unwrap().unix_time();
+    assert!(Timestamp::from_unix_time(max + 60).is_none());
+    let min = Timestamp::from_str("000001010000").unwrap().unix_time();
+    assert!(Timestamp::from_unix_time(min - 60).is_none());
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn test_timestamp_chrono() {
+    use chrono::{DateTime, TimeZone, Utc};
+
+    let ts = Timestamp::from_str("202501101350").unwrap();
+    let dt: DateTime<Utc> = ts.into();
+    assert_eq!(dt, Utc.with_ymd_and_hms(2025, 1, 10, 13, 50, 0).unwrap());
+    assert_eq!(ts.to_datetime(), dt);
+    assert_eq!(Timestamp::try_from(dt).unwrap(), ts);
+
+    let dt = Utc.with_ymd_and_hms(10000, 1, 1, 0, 0, 0).unwrap();
+    assert!(Timestamp::try_from(dt).is_err());
+}
+
+// Checking the (de)serialization as the string representation.
+#[cfg(feature = "serde")]
+#[test]
+fn test_timestamp_serde() {
+    let ts = Timestamp::from_str("202501101350").unwrap();
+    assert_eq!(serde_json::to_string(&ts).unwrap(), "\"202501101350\"");
+    assert_eq!(serde_json::from_str::<Timestamp>("\"202501101350\"").unwrap(), ts);
+    assert!(serde_json::from_str::<Timestamp>("\"202502301350\"").is_err());
+}
@@ -0,0 +1,228 @@
+/*
+    Created by Zoltan Kovari, 2025.
+
+    Licensed under the Apache License, Version 2.0
+    http://www.apache.org/licenses/LICENSE-2.0
+    (see LICENSE.txt)
+*/
+
+//! Types for reporting the results of `validate_csv`.
+
+use crate::field::Field;
+use crate::format::CsvFormat;
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fmt::Display;
+
+/// Categories of problems found in an input line.
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum IssueKind {
+    /// The number of fields differs from the header.
+    FieldCount,
+    /// `Time` or `StationNumber` is missing.
+    MissingMandatory,
+    /// A field is not padded to its width, although the header is aligned.
+    Misaligned,
+    /// A field is padded, although the header is condensed.
+    Padded,
+    /// A value could not be converted to its data type (see `MetRecord`).
+    InvalidValue,
+    /// The same `Time` and `StationNumber` pair already occurred in an earlier line.
+    Duplicate,
+}
+impl IssueKind {
+    /// Identifier used in the text and JSON outputs.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::FieldCount => "field_count",
+            Self::MissingMandatory => "missing_mandatory",
+            Self::Misaligned => "misaligned",
+            Self::Padded => "padded",
+            Self::InvalidValue => "invalid_value",
+            Self::Duplicate => "duplicate",
+        }
+    }
+}
+impl Display for IssueKind {
+    /// Prints the `name`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// A problem found in an input line.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Issue {
+    /// Line number, starting from 1 (which is the header).
+    pub line: usize,
+    /// Station number, if it could be determined.
+    pub station: Option<String>,
+    /// The offending field, if the problem is specific to one.
+    pub field: Option<Field>,
+    pub kind: IssueKind,
+    /// Description of the problem.
+    pub message: String,
+}
+
+/// Results of `validate_csv`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ValidationReport {
+    /// Format detected from the header, which the lines are checked against.
+    pub format: CsvFormat,
+    /// Number of data lines (i.e. not counting the header and empty lines).
+    pub lines: usize,
+    /// Number of lines that did not satisfy the `RecordFilter` (these only had basic checks).
+    pub filtered: usize,
+    /// Every problem found, in the order of the lines.
+    pub issues: Vec<Issue>,
+}
+impl ValidationReport {
+    /// Constructs an empty report for the format.
+    pub(crate) fn new(format: CsvFormat) -> Self {
+        Self {
+            format,
+            lines: 0,
+            filtered: 0,
+            issues: Vec::new(),
+        }
+    }
+
+    /// Returns `true` if no problems were found.
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Number of lines with at least one problem.
+    pub fn invalid_lines(&self) -> usize {
+        self.issues
+            .iter()
+            .map(|i| i.line)
+            .collect::<BTreeSet<usize>>()
+            .len()
+    }
+
+    /// Number of issues per kind, only the kinds that occurred are included.
+    pub fn counts(&self) -> BTreeMap<IssueKind, usize> {
+        let mut counts = BTreeMap::new();
+        for issue in &self.issues {
+            *counts.entry(issue.kind).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Renders the report as a JSON object.
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("{");
+        json.push_str(&format!(
+            "\"format\":{{\"alignment\":{},\"delimiter\":{},\"fields\":[{}]}},",
+            self.format.alignment,
+            json_string(&self.format.delimiter.to_string()),
+            self.format
+                .field_config
+                .fields()
+                .map(|f| json_string(f.title()))
+                .collect::<Vec<String>>()
+                .join(","),
+        ));
+        json.push_str(&format!(
+            "\"summary\":{{\"lines\":{},\"valid\":{},\"invalid\":{},\"filtered\":{}}},",
+            self.lines,
+            self.lines - self.invalid_lines(),
+            self.invalid_lines(),
+            self.filtered,
+        ));
+        json.push_str(&format!(
+            "\"counts\":{{{}}},",
+            self.counts()
+                .iter()
+                .map(|(kind, count)| format!("{}:{}", json_string(kind.name()), count))
+                .collect::<Vec<String>>()
+                .join(","),
+        ));
+
+        let optional = |value: Option<&str>| match value {
+            Some(s) => json_string(s),
+            None => "null".to_string(),
+        };
+        json.push_str(&format!(
+            "\"issues\":[{}]",
+            self.issues
+                .iter()
+                .map(|i| format!(
+                    "{{\"line\":{},\"station\":{},\"field\":{},\"kind\":{},\"message\":{}}}",
+                    i.line,
+                    optional(i.station.as_deref()),
+                    optional(i.field.as_ref().map(|f| f.title())),
+                    json_string(i.kind.name()),
+                    json_string(&i.message),
+                ))
+                .collect::<Vec<String>>()
+                .join(","),
+        ));
+        json.push('}');
+
+        json
+    }
+}
+impl Display for ValidationReport {
+    /// Prints the report as human-readable text.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Format: {}, delimiter '{}', {} fields",
+            if self.format.alignment { "aligned" } else { "condensed" },
+            self.format.delimiter.to_string().escape_debug(),
+            self.format.field_config.fields().count(),
+        )?;
+        writeln!(
+            f,
+            "Lines: {}, valid: {}, invalid: {}, filtered out: {}",
+            self.lines,
+            self.lines - self.invalid_lines(),
+            self.invalid_lines(),
+            self.filtered,
+        )?;
+
+        if self.is_valid() {
+            return writeln!(f, "No issues found.");
+        }
+
+        writeln!(f, "Issues by kind:")?;
+        for (kind, count) in self.counts() {
+            writeln!(f, "  {}: {}", kind, count)?;
+        }
+        writeln!(f, "Issues:")?;
+        for issue in &self.issues {
+            write!(f, "  line {}", issue.line)?;
+            if let Some(station) = &issue.station {
+                write!(f, ", station {}", station)?;
+            }
+            if let Some(field) = &issue.field {
+                write!(f, ", field `{}`", field)?;
+            }
+            writeln!(f, ": {} ({})", issue.message, issue.kind)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Quotes and escapes a string for JSON.
+fn json_string(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for ch in s.chars() {
+        match ch {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            ch if ch.is_control() => quoted.push_str(&format!("\\u{:04x}", ch as u32)),
+            ch => quoted.push(ch),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
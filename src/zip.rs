@@ -6,14 +6,146 @@
     (see LICENSE.txt)
 */
 
-use std::io::{BufRead, Read, Seek, Write};
+//! Functions for handling the zip archives that ODP uses to distribute the CSV files.
 
+use crate::error::*;
+
+use std::io::{BufRead, Cursor, Read, Seek, Write};
+
+use zip::read::read_zipfile_from_stream;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+#[cfg(test)]
+pub(crate) mod test;
+
+/// Reads an archive that contains a single CSV file (as distributed on ODP), and returns a
+/// reader of the decompressed content.
+///
+/// The content is decompressed in memory, nothing is extracted to disk. The reader does not
+/// need to seek, see `unzip_stream`.
 pub fn unzip(zip_reader: impl Read) -> Result<Box<dyn BufRead>, ZipError> {
-    todo!()
+    Ok(unzip_stream(zip_reader)?.1)
+}
+
+/// Same as `unzip`, also returning the name of the member.
+///
+/// On ODP, the name contains the creation time of the file (e.g.
+/// `HABP_10M_SYNOP_20250110140805.csv`), while the archive itself is named `..._LATEST`.
+pub fn unzip_named(zip_reader: impl Read + Seek) -> Result<(String, Box<dyn BufRead>), ZipError> {
+    let mut archive = ZipArchive::new(zip_reader).map_err(ZipError::from)?;
+    if archive.len() != 1 {
+        return Err(ZipError::MemberCount(archive.len()));
+    }
+
+    let method = archive.by_index_raw(0)?.compression();
+    if !matches!(method, CompressionMethod::Stored | CompressionMethod::Deflated) {
+        return Err(ZipError::UnsupportedCompression(method.to_string()));
+    }
+
+    let member = archive.by_index(0)?;
+    let name = member.name().to_string();
+    let size = member.size();
+    let content = read_member(member, size)?;
+
+    Ok((name, Box::new(Cursor::new(content))))
+}
+
+/// Upper limit of the buffer allocated in advance, as the size in the header is not trusted.
+const MAX_PREALLOCATION: u64 = 64 * 1024 * 1024;
+
+/// Reads the decompressed content of a member, whose declared size is `size`.
+///
+/// Checksum and decompression failures count as `Corrupt`, not as I/O errors.
+fn read_member(mut member: impl Read, size: u64) -> Result<Vec<u8>, ZipError> {
+    let mut content = Vec::with_capacity(size.min(MAX_PREALLOCATION) as usize);
+    match member.read_to_end(&mut content) {
+        Ok(_) => Ok(content),
+        Err(e) if is_corrupt(&e) => Err(ZipError::Corrupt(zip::result::ZipError::Io(e))),
+        Err(e) => Err(ZipError::Io(e)),
+    }
 }
 
-fn zip(csv_reader: impl Read, zip_writer: impl Write + Seek) {
-    todo!()
+/// Whether an error of reading a member is caused by its content (failed checksum, invalid or
+/// incomplete compressed data).
+fn is_corrupt(e: &std::io::Error) -> bool {
+    use std::io::ErrorKind::*;
+    matches!(e.kind(), InvalidData | InvalidInput | UnexpectedEof)
+}
+
+/// Same as `unzip_named`, for a reader that cannot seek, like `STDIN`.
+///
+/// The local file headers are read sequentially, so the content is available without reading
+/// the whole archive first. The rest of the input is consumed to check that there are no other
+/// members. If the sizes are only stored after the content (in a data descriptor, e.g. in
+/// archives written to a pipe), the central directory is required, so the input is spooled into
+/// memory and read with `unzip_named`.
+pub fn unzip_stream(mut zip_reader: impl Read) -> Result<(String, Box<dyn BufRead>), ZipError> {
+    let mut header = Vec::with_capacity(LOCAL_HEADER_LEN);
+    (&mut zip_reader)
+        .take(LOCAL_HEADER_LEN as u64)
+        .read_to_end(&mut header)
+        .map_err(ZipError::Io)?;
+    if header.len() < LOCAL_HEADER_LEN {
+        return Err(ZipError::Corrupt(zip::result::ZipError::InvalidArchive(
+            "Missing local file header",
+        )));
+    }
+    if header.starts_with(b"PK\x03\x04") {
+        // Checked before decompression, which would fail with a less specific error
+        let method = u16::from_le_bytes([header[8], header[9]]);
+        if !matches!(method, STORED | DEFLATED) {
+            return Err(ZipError::UnsupportedCompression(format!("method {}", method)));
+        }
+        if header[6] & DATA_DESCRIPTOR_FLAG != 0 {
+            zip_reader.read_to_end(&mut header).map_err(ZipError::Io)?;
+            return unzip_named(Cursor::new(header));
+        }
+    }
+
+    let mut reader = Cursor::new(header).chain(zip_reader);
+    let (name, content) = {
+        let Some(mut member) = read_zipfile_from_stream(&mut reader)? else {
+            return Err(ZipError::MemberCount(0));
+        };
+        let name = member.name().to_string();
+        let size = member.size();
+        (name, read_member(&mut member, size)?)
+    };
+
+    let mut count = 1;
+    while read_zipfile_from_stream(&mut reader)?.is_some() {
+        count += 1;
+    }
+    if count != 1 {
+        return Err(ZipError::MemberCount(count));
+    }
+    // The central directory is not needed, but the writer of a pipe may expect it to be read
+    std::io::copy(&mut reader, &mut std::io::sink()).map_err(ZipError::Io)?;
+
+    Ok((name, Box::new(Cursor::new(content))))
 }
 
-pub struct ZipError;
+/// Length of the fixed part of a local file header.
+const LOCAL_HEADER_LEN: usize = 30;
+/// Bit of the general purpose flags (in the header at offset 6) marking a data descriptor.
+const DATA_DESCRIPTOR_FLAG: u8 = 1 << 3;
+/// Values of the compression method field (in the header at offset 8).
+const STORED: u16 = 0;
+const DEFLATED: u16 = 8;
+
+/// Creates an archive with a single member named `name`, holding everything read from
+/// `csv_reader`, compressed with the Deflate method.
+pub fn zip(
+    mut csv_reader: impl Read,
+    zip_writer: impl Write + Seek,
+    name: &str,
+) -> Result<(), ZipError> {
+    let mut writer = ZipWriter::new(zip_writer);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated);
+    writer.start_file(name, options)?;
+    std::io::copy(&mut csv_reader, &mut writer).map_err(ZipError::Io)?;
+    writer.finish()?;
+
+    Ok(())
+}
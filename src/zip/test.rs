@@ -0,0 +1,176 @@
+/*
+    Created by Zoltan Kovari, 2025.
+
+    Licensed under the Apache License, Version 2.0
+    http://www.apache.org/licenses/LICENSE-2.0
+    (see LICENSE.txt)
+*/
+
+use super::*;
+
+use crate::record::test::TEST_CSV;
+
+pub(crate) const TEST_ZIP: &[u8] = include_bytes!("../../tests/HABP_10M_SYNOP_LATEST.csv.zip");
+
+/// Builds an archive with a single stored (uncompressed) member "a.csv", holding a CSV header.
+fn stored_archive() -> Vec<u8> {
+    let mut archive = Cursor::new(Vec::new());
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Stored);
+    let mut writer = ZipWriter::new(&mut archive);
+    writer.start_file("a.csv", options).unwrap();
+    writer.write_all(b"Time;StationNumber\r\n").unwrap();
+    writer.finish().unwrap();
+    archive.into_inner()
+}
+
+/// Returns the sample archive with the beginning of its deflate stream overwritten.
+fn corrupt_deflate() -> Vec<u8> {
+    let mut bytes = TEST_ZIP.to_vec();
+    let len = |at: usize| u16::from_le_bytes([bytes[at], bytes[at + 1]]) as usize;
+    let data = 30 + len(26) + len(28);
+    bytes[data..data + 16].fill(0xff);
+    bytes
+}
+
+// Checking that the sample archive is decompressed to the sample CSV.
+#[test]
+fn test_unzip() {
+    let mut content = String::new();
+    unzip(Cursor::new(TEST_ZIP))
+        .unwrap()
+        .read_to_string(&mut content)
+        .unwrap();
+    assert_eq!(content, TEST_CSV);
+}
+
+// Checking that an archive created by `zip` can be read back.
+#[test]
+fn test_zip_roundtrip() {
+    let mut archive = Cursor::new(Vec::new());
+    zip(TEST_CSV.as_bytes(), &mut archive, "test.csv").unwrap();
+
+    let mut content = String::new();
+    archive.set_position(0);
+    let (name, mut reader) = unzip_named(archive).unwrap();
+    reader.read_to_string(&mut content).unwrap();
+    assert_eq!(name, "test.csv");
+    assert_eq!(content, TEST_CSV);
+}
+
+// Checking the error variants.
+#[test]
+fn test_unzip_errors() {
+    assert!(matches!(
+        unzip(Cursor::new(&TEST_ZIP[..TEST_ZIP.len() / 2])),
+        Err(ZipError::Corrupt(_))
+    ));
+    assert!(matches!(
+        unzip(Cursor::new(b"not a zip")),
+        Err(ZipError::Corrupt(_))
+    ));
+
+    // Two members
+    let mut archive = Cursor::new(Vec::new());
+    let mut writer = ZipWriter::new(&mut archive);
+    for name in ["a.csv", "b.csv"] {
+        writer.start_file(name, zip::write::SimpleFileOptions::default()).unwrap();
+        writer.write_all(b"Time;StationNumber\r\n").unwrap();
+    }
+    writer.finish().unwrap();
+    archive.set_position(0);
+    assert!(matches!(unzip(archive), Err(ZipError::MemberCount(2))));
+
+    // Patching the compression method (in both the local and central headers) to BZIP2
+    let mut bytes = stored_archive();
+    bytes[8] = 12;
+    let central = bytes
+        .windows(4)
+        .position(|w| w == b"PK\x01\x02")
+        .unwrap();
+    bytes[central + 10] = 12;
+    assert!(matches!(
+        unzip(Cursor::new(bytes)),
+        Err(ZipError::UnsupportedCompression(_))
+    ));
+}
+
+// Checking that damaged content is reported as `Corrupt`, and that the declared size is not
+// trusted.
+#[test]
+fn test_unzip_corrupt() {
+    // Failing the checksum
+    let mut bytes = stored_archive();
+    let content = bytes.windows(4).position(|w| w == b"Time").unwrap();
+    bytes[content] = b'X';
+    assert!(matches!(unzip(Cursor::new(bytes)), Err(ZipError::Corrupt(_))));
+
+    // Invalid deflate stream
+    let bytes = corrupt_deflate();
+    assert!(matches!(unzip(Cursor::new(bytes)), Err(ZipError::Corrupt(_))));
+
+    // Declaring a huge size (in the central header) does not allocate it
+    let mut bytes = stored_archive();
+    let central = bytes.windows(4).position(|w| w == b"PK\x01\x02").unwrap();
+    bytes[central + 24..central + 28].copy_from_slice(&0xfffffff0_u32.to_le_bytes());
+    let mut content = String::new();
+    unzip(Cursor::new(bytes)).unwrap().read_to_string(&mut content).unwrap();
+    assert_eq!(content, "Time;StationNumber\r\n");
+}
+
+// Checking the sequential reading from a reader that cannot seek.
+#[test]
+fn test_unzip_stream() {
+    let mut content = String::new();
+    let (name, mut reader) = unzip_stream(TEST_ZIP).unwrap();
+    reader.read_to_string(&mut content).unwrap();
+    assert_eq!(name, "HABP_10M_SYNOP_20250110140805.csv");
+    assert_eq!(content, TEST_CSV);
+
+    // Flagging a data descriptor (in the local header), so the archive is spooled
+    let mut bytes = TEST_ZIP.to_vec();
+    bytes[6] |= DATA_DESCRIPTOR_FLAG;
+    let mut content = String::new();
+    let (name, mut reader) = unzip_stream(&bytes[..]).unwrap();
+    reader.read_to_string(&mut content).unwrap();
+    assert_eq!(name, "HABP_10M_SYNOP_20250110140805.csv");
+    assert_eq!(content, TEST_CSV);
+
+    assert!(unzip_stream(&TEST_ZIP[..TEST_ZIP.len() / 2]).is_err());
+    assert!(matches!(unzip_stream(&b"not a zip"[..]), Err(ZipError::Corrupt(_))));
+    assert!(matches!(
+        unzip_stream(&TEST_CSV.as_bytes()[..100]),
+        Err(ZipError::Corrupt(_))
+    ));
+
+    let mut archive = Cursor::new(Vec::new());
+    let mut writer = ZipWriter::new(&mut archive);
+    for name in ["a.csv", "b.csv", "c.csv"] {
+        writer.start_file(name, zip::write::SimpleFileOptions::default()).unwrap();
+        writer.write_all(b"Time;StationNumber\r\n").unwrap();
+    }
+    writer.finish().unwrap();
+    assert!(matches!(
+        unzip_stream(&archive.get_ref()[..]),
+        Err(ZipError::MemberCount(3))
+    ));
+
+    // Patching the compression method (in the local header) to BZIP2
+    let mut bytes = stored_archive();
+    bytes[8] = 12;
+    assert!(matches!(
+        unzip_stream(&bytes[..]),
+        Err(ZipError::UnsupportedCompression(_))
+    ));
+
+    // Invalid deflate stream
+    let bytes = corrupt_deflate();
+    assert!(matches!(unzip_stream(&bytes[..]), Err(ZipError::Corrupt(_))));
+
+    // Declaring a huge size (in the local header) does not allocate it
+    let mut bytes = stored_archive();
+    bytes[22..26].copy_from_slice(&0xfffffff0_u32.to_le_bytes());
+    let mut content = String::new();
+    unzip_stream(&bytes[..]).unwrap().1.read_to_string(&mut content).unwrap();
+    assert_eq!(content, "Time;StationNumber\r\n");
+}